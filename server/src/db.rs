@@ -0,0 +1,955 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+
+use dotdotvote_types::v1::{
+    AdvancePhase, CreatePoll, Invite, NewChoice, Participant, Poll, PollChoice, PollMetadata,
+    PollPhase, PollResults, ResultsViewer, UpdatePoll, Vote, VoteSubmission,
+};
+use dotdotvote_types::{
+    AdminToken, BallotId, ChoiceId, EditToken, InviteToken, PollId, PollUuid, Timestamp, VoteId,
+};
+
+use crate::error::AppError;
+
+/// Schema changes, applied in order. `PRAGMA user_version` records how many have run, so new
+/// migrations must only ever be appended.
+const MIGRATIONS: &[&str] = &[
+    r#"
+    CREATE TABLE polls (
+        id INTEGER PRIMARY KEY,
+        uuid TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        dot_budget TEXT NOT NULL,
+        rules TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    CREATE TABLE choices (
+        id INTEGER PRIMARY KEY,
+        poll_id INTEGER NOT NULL REFERENCES polls(id) ON DELETE CASCADE,
+        details TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    CREATE TABLE votes (
+        id INTEGER PRIMARY KEY,
+        poll_id INTEGER NOT NULL REFERENCES polls(id) ON DELETE CASCADE,
+        choice_id INTEGER NOT NULL REFERENCES choices(id) ON DELETE CASCADE,
+        dots INTEGER NOT NULL,
+        voter TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    CREATE INDEX choices_poll_id ON choices(poll_id);
+    CREATE INDEX votes_poll_id ON votes(poll_id);
+"#,
+    r#"
+    ALTER TABLE polls ADD COLUMN voting_method TEXT NOT NULL DEFAULT '"dots"';
+"#,
+    r#"
+    ALTER TABLE polls ADD COLUMN funding INTEGER;
+    ALTER TABLE choices ADD COLUMN cost INTEGER;
+"#,
+    r#"
+    ALTER TABLE votes ADD COLUMN edit_token TEXT;
+    CREATE INDEX votes_edit_token ON votes(edit_token);
+"#,
+    r#"
+    ALTER TABLE polls ADD COLUMN opens_at TEXT;
+    ALTER TABLE polls ADD COLUMN closes_at TEXT;
+    ALTER TABLE polls ADD COLUMN admin_token TEXT;
+"#,
+    r#"
+    ALTER TABLE polls ADD COLUMN phase TEXT;
+"#,
+    r#"
+    ALTER TABLE polls ADD COLUMN categories TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE choices ADD COLUMN category INTEGER;
+"#,
+    r#"
+    ALTER TABLE polls ADD COLUMN results_visibility TEXT NOT NULL DEFAULT '"always"';
+"#,
+    r#"
+    ALTER TABLE polls ADD COLUMN anonymous INTEGER NOT NULL DEFAULT 0;
+"#,
+    r#"
+    ALTER TABLE polls ADD COLUMN invite_only INTEGER NOT NULL DEFAULT 0;
+    CREATE TABLE invites (
+        id INTEGER PRIMARY KEY,
+        poll_id INTEGER NOT NULL REFERENCES polls(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        token TEXT NOT NULL UNIQUE,
+        edit_token TEXT
+    );
+    CREATE INDEX invites_poll_id ON invites(poll_id);
+"#,
+];
+
+/// A shared SQLite connection. Queries run on the blocking thread pool so they don't hold up
+/// the async runtime.
+#[derive(Clone)]
+pub struct Db {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Db {
+    pub fn open<P: AsRef<Path>>(path: P) -> rusqlite::Result<Self> {
+        let mut conn = Connection::open(path)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut conn)?;
+        Ok(Db {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    pub async fn call<F, T>(&self, f: F) -> Result<T, AppError>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, AppError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn
+                .lock()
+                .map_err(|_| AppError::Internal("database lock poisoned".into()))?;
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+    }
+}
+
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", (i + 1) as i64)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+pub fn now() -> Timestamp {
+    Timestamp::from(chrono::Utc::now())
+}
+
+fn conversion_error<E>(i: usize, error: E) -> rusqlite::Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    rusqlite::Error::FromSqlConversionFailure(i, Type::Text, Box::new(error))
+}
+
+fn timestamp_column(row: &Row, i: usize) -> rusqlite::Result<Timestamp> {
+    let text: String = row.get(i)?;
+    text.parse().map_err(|e| conversion_error(i, e))
+}
+
+fn optional_timestamp_column(row: &Row, i: usize) -> rusqlite::Result<Option<Timestamp>> {
+    let text: Option<String> = row.get(i)?;
+    text.map(|text| text.parse().map_err(|e| conversion_error(i, e)))
+        .transpose()
+}
+
+/// Reads a column holding JSON, used for poll settings that don't need to be queried on.
+fn json_column<T: DeserializeOwned>(row: &Row, i: usize) -> rusqlite::Result<T> {
+    let text: String = row.get(i)?;
+    serde_json::from_str(&text).map_err(|e| conversion_error(i, e))
+}
+
+fn optional_json_column<T: DeserializeOwned>(row: &Row, i: usize) -> rusqlite::Result<Option<T>> {
+    let text: Option<String> = row.get(i)?;
+    text.map(|text| serde_json::from_str(&text).map_err(|e| conversion_error(i, e)))
+        .transpose()
+}
+
+fn poll_metadata(row: &Row) -> rusqlite::Result<PollMetadata> {
+    Ok(PollMetadata {
+        id: PollId(row.get(0)?),
+        uuid: PollUuid(row.get(1)?),
+        title: row.get(2)?,
+        created_at: timestamp_column(row, 3)?,
+        dot_budget: json_column(row, 4)?,
+        rules: json_column(row, 5)?,
+        voting_method: json_column(row, 6)?,
+        funding: row.get(7)?,
+        opens_at: optional_timestamp_column(row, 8)?,
+        closes_at: optional_timestamp_column(row, 9)?,
+        phase: optional_json_column(row, 10)?,
+        categories: json_column(row, 11)?,
+        results_visibility: json_column(row, 12)?,
+        anonymous: row.get(13)?,
+        invite_only: row.get(14)?,
+    })
+}
+
+fn find_metadata(conn: &Connection, uuid: &PollUuid) -> Result<PollMetadata, AppError> {
+    conn.query_row(
+        "SELECT id, uuid, title, created_at, dot_budget, rules, voting_method, funding, opens_at, closes_at, phase, categories, results_visibility, anonymous, invite_only FROM polls WHERE uuid = ?1",
+        params![uuid.as_str()],
+        poll_metadata,
+    )
+    .optional()?
+    .ok_or(AppError::NotFound)
+}
+
+fn find_choices(conn: &Connection, poll_id: PollId) -> Result<Vec<PollChoice>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, poll_id, details, created_at, cost, category FROM choices WHERE poll_id = ?1 ORDER BY id",
+    )?;
+    let choices = stmt
+        .query_map(params![poll_id.0], |row| {
+            Ok(PollChoice {
+                id: ChoiceId(row.get(0)?),
+                poll_id: PollId(row.get(1)?),
+                details: row.get(2)?,
+                created_at: timestamp_column(row, 3)?,
+                cost: row.get(4)?,
+                category: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(choices)
+}
+
+pub fn find_poll(conn: &Connection, uuid: &PollUuid) -> Result<Poll, AppError> {
+    let poll = find_metadata(conn, uuid)?;
+    let choices = find_choices(conn, poll.id)?;
+    Ok(Poll { poll, choices })
+}
+
+/// Creates a poll, returning its uuid, the admin token that manages it and an invite for each
+/// name on its roster.
+pub fn insert_poll(
+    conn: &mut Connection,
+    create: &CreatePoll,
+) -> Result<(PollUuid, AdminToken, Vec<Invite>), AppError> {
+    let uuid = PollUuid(uuid::Uuid::new_v4().to_string());
+    let admin_token = AdminToken(uuid::Uuid::new_v4().to_string());
+    let created_at = now();
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO polls (uuid, title, dot_budget, rules, voting_method, funding, opens_at, closes_at, phase, categories, results_visibility, anonymous, invite_only, admin_token, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            uuid.as_str(),
+            create.title.trim(),
+            serde_json::to_string(&create.dot_budget)?,
+            serde_json::to_string(&create.rules)?,
+            serde_json::to_string(&create.voting_method)?,
+            create.funding,
+            create.opens_at.map(|time| time.to_string()),
+            create.closes_at.map(|time| time.to_string()),
+            create
+                .brainstorm
+                .then(|| serde_json::to_string(&PollPhase::Collect))
+                .transpose()?,
+            serde_json::to_string(&create.categories)?,
+            serde_json::to_string(&create.results_visibility)?,
+            create.anonymous,
+            create.roster_names().next().is_some(),
+            admin_token.as_str(),
+            created_at.to_string()
+        ],
+    )?;
+    let poll_id = tx.last_insert_rowid();
+    for (details, cost, category) in create.new_choices() {
+        tx.execute(
+            "INSERT INTO choices (poll_id, details, cost, category, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![poll_id, details, cost, category, created_at.to_string()],
+        )?;
+    }
+    let mut invites = Vec::new();
+    for name in create.roster_names() {
+        let token = InviteToken(uuid::Uuid::new_v4().to_string());
+        tx.execute(
+            "INSERT INTO invites (poll_id, name, token) VALUES (?1, ?2, ?3)",
+            params![poll_id, name, token.as_str()],
+        )?;
+        invites.push(Invite {
+            name: name.to_owned(),
+            token,
+        });
+    }
+    tx.commit()?;
+    Ok((uuid, admin_token, invites))
+}
+
+/// Whether the invite's ballot is still on the poll. Withdrawn or deleted ballots leave the
+/// invite free to vote again.
+const INVITE_VOTED: &str =
+    "EXISTS(SELECT 1 FROM votes WHERE votes.poll_id = invites.poll_id AND votes.edit_token = invites.edit_token)";
+
+/// Finds who an invite is for and whether they've voted.
+pub fn find_invite(
+    conn: &Connection,
+    poll: &Poll,
+    token: &InviteToken,
+) -> Result<Participant, AppError> {
+    conn.query_row(
+        &format!(
+            "SELECT name, {} FROM invites WHERE poll_id = ?1 AND token = ?2",
+            INVITE_VOTED
+        ),
+        params![poll.poll.id.0, token.as_str()],
+        participant,
+    )
+    .optional()?
+    .ok_or_else(invite_mismatch)
+}
+
+fn participant(row: &Row) -> rusqlite::Result<Participant> {
+    Ok(Participant {
+        name: row.get(0)?,
+        voted: row.get(1)?,
+    })
+}
+
+fn invite_mismatch() -> AppError {
+    AppError::Forbidden("that invite doesn't match this poll".into())
+}
+
+/// Every invite on a poll, in roster order.
+pub fn find_invites(conn: &Connection, poll: &Poll) -> Result<Vec<Invite>, AppError> {
+    let mut stmt =
+        conn.prepare("SELECT name, token FROM invites WHERE poll_id = ?1 ORDER BY id")?;
+    let invites = stmt
+        .query_map(params![poll.poll.id.0], |row| {
+            Ok(Invite {
+                name: row.get(0)?,
+                token: InviteToken(row.get(1)?),
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(invites)
+}
+
+fn find_participation(
+    conn: &Connection,
+    poll: &PollMetadata,
+) -> Result<Vec<Participant>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT name, {} FROM invites WHERE poll_id = ?1 ORDER BY id",
+        INVITE_VOTED
+    ))?;
+    let participation = stmt
+        .query_map(params![poll.id.0], participant)?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(participation)
+}
+
+fn find_admin_token(conn: &Connection, uuid: &PollUuid) -> Result<Option<String>, AppError> {
+    conn.query_row(
+        "SELECT admin_token FROM polls WHERE uuid = ?1",
+        params![uuid.as_str()],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or(AppError::NotFound)
+}
+
+/// Works out who holding `token` makes someone, to decide whether they may see the results.
+/// Tokens that match nothing are ignored.
+pub fn find_viewer(
+    conn: &Connection,
+    poll: &PollMetadata,
+    token: Option<&str>,
+) -> Result<ResultsViewer, AppError> {
+    let token = match token {
+        Some(token) => token,
+        None => return Ok(ResultsViewer::Anyone),
+    };
+    if find_admin_token(conn, &poll.uuid)?.as_deref() == Some(token) {
+        return Ok(ResultsViewer::Creator);
+    }
+    let voted: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM votes WHERE poll_id = ?1 AND edit_token = ?2)",
+        params![poll.id.0, token],
+        |row| row.get(0),
+    )?;
+    Ok(if voted {
+        ResultsViewer::Voter
+    } else {
+        ResultsViewer::Anyone
+    })
+}
+
+/// Finds a poll for its creator, failing unless `token` is its admin token.
+pub fn find_admin_poll(
+    conn: &Connection,
+    uuid: &PollUuid,
+    token: &AdminToken,
+) -> Result<Poll, AppError> {
+    if find_admin_token(conn, uuid)?.as_deref() != Some(token.as_str()) {
+        return Err(AppError::Forbidden(
+            "that admin token doesn't match this poll".into(),
+        ));
+    }
+    find_poll(conn, uuid)
+}
+
+/// Ends voting on a poll now.
+pub fn close_poll(conn: &Connection, poll: &mut Poll) -> Result<(), AppError> {
+    let closes_at = now();
+    conn.execute(
+        "UPDATE polls SET closes_at = ?1 WHERE id = ?2",
+        params![closes_at.to_string(), poll.poll.id.0],
+    )?;
+    poll.poll.closes_at = Some(closes_at);
+    Ok(())
+}
+
+/// Opens a closed poll back up with no closing time.
+pub fn reopen_poll(conn: &Connection, poll: &mut Poll) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE polls SET closes_at = NULL WHERE id = ?1",
+        params![poll.poll.id.0],
+    )?;
+    poll.poll.closes_at = None;
+    Ok(())
+}
+
+/// Adds a participant's choice to a poll that's collecting them.
+pub fn insert_choice(conn: &Connection, poll: &Poll, new: &NewChoice) -> Result<Poll, AppError> {
+    conn.execute(
+        "INSERT INTO choices (poll_id, details, category, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            poll.poll.id.0,
+            new.details.trim(),
+            new.category,
+            now().to_string()
+        ],
+    )?;
+    find_poll(conn, &poll.poll.uuid)
+}
+
+/// Moves a brainstorming poll on to its next phase, folding merged choices into the ones they
+/// duplicate, and returns the poll as it now stands.
+pub fn advance_phase(
+    conn: &mut Connection,
+    poll: &Poll,
+    advance: &AdvancePhase,
+) -> Result<Poll, AppError> {
+    let tx = conn.transaction()?;
+    tx.execute(
+        "UPDATE polls SET phase = ?1 WHERE id = ?2",
+        params![serde_json::to_string(&advance.phase)?, poll.poll.id.0],
+    )?;
+    for merge in &advance.merges {
+        for choice in &merge.from {
+            tx.execute(
+                "DELETE FROM choices WHERE id = ?1 AND poll_id = ?2",
+                params![choice.0, poll.poll.id.0],
+            )?;
+        }
+    }
+    tx.commit()?;
+    find_poll(conn, &poll.poll.uuid)
+}
+
+/// Applies a creator's edit, returning the poll as it now stands.
+pub fn update_poll(
+    conn: &mut Connection,
+    poll: &Poll,
+    update: &UpdatePoll,
+) -> Result<Poll, AppError> {
+    let created_at = now();
+    let tx = conn.transaction()?;
+    tx.execute(
+        "UPDATE polls SET title = ?1 WHERE id = ?2",
+        params![update.title.trim(), poll.poll.id.0],
+    )?;
+    for choice in &poll.choices {
+        if !update
+            .listed_choices()
+            .any(|listed| listed.id == Some(choice.id))
+        {
+            // Its votes go with it
+            tx.execute("DELETE FROM choices WHERE id = ?1", params![choice.id.0])?;
+        }
+    }
+    for choice in update.listed_choices() {
+        match choice.id {
+            Some(id) => tx.execute(
+                "UPDATE choices SET details = ?1, cost = ?2, category = ?3 WHERE id = ?4 AND poll_id = ?5",
+                params![
+                    choice.details.trim(),
+                    choice.cost,
+                    choice.category,
+                    id.0,
+                    poll.poll.id.0
+                ],
+            )?,
+            None => tx.execute(
+                "INSERT INTO choices (poll_id, details, cost, category, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    poll.poll.id.0,
+                    choice.details.trim(),
+                    choice.cost,
+                    choice.category,
+                    created_at.to_string()
+                ],
+            )?,
+        };
+    }
+    tx.commit()?;
+    find_poll(conn, &poll.poll.uuid)
+}
+
+/// Removes the whole ballot `vote_id` was cast with, returning the rows it was. Ballots from
+/// before edit tokens are told apart by voter and time.
+pub fn delete_ballot_of(
+    conn: &mut Connection,
+    poll: &Poll,
+    vote_id: VoteId,
+) -> Result<Vec<VoteId>, AppError> {
+    let tx = conn.transaction()?;
+    let (token, voter, created_at): (Option<String>, String, String) = tx
+        .query_row(
+            "SELECT edit_token, voter, created_at FROM votes WHERE id = ?1 AND poll_id = ?2",
+            params![vote_id.0, poll.poll.id.0],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::invalid("vote", "isn't a vote on this poll"))?;
+    let removed = {
+        let mut stmt = tx.prepare(
+            "DELETE FROM votes WHERE poll_id = ?1 AND (edit_token = ?2 OR (edit_token IS NULL AND ?2 IS NULL AND voter = ?3 AND created_at = ?4)) RETURNING id",
+        )?;
+        let removed = stmt
+            .query_map(params![poll.poll.id.0, token, voter, created_at], |row| {
+                Ok(VoteId(row.get(0)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        removed
+    };
+    tx.commit()?;
+    Ok(removed)
+}
+
+/// Deletes a poll with its choices and votes.
+pub fn delete_poll(conn: &Connection, poll: &Poll) -> Result<(), AppError> {
+    conn.execute("DELETE FROM polls WHERE id = ?1", params![poll.poll.id.0])?;
+    Ok(())
+}
+
+/// Records a ballot, returning its edit token and the vote rows it became.
+pub fn insert_vote(
+    conn: &mut Connection,
+    poll: &Poll,
+    vote: &VoteSubmission,
+) -> Result<(EditToken, Vec<Vote>), AppError> {
+    let token = EditToken(uuid::Uuid::new_v4().to_string());
+    let tx = conn.transaction()?;
+    if poll.poll.invite_only {
+        let invite = vote.invite.as_ref().ok_or_else(|| {
+            AppError::Forbidden("this poll is invite only, vote through your invite link".into())
+        })?;
+        if find_invite(&tx, poll, invite)?.voted {
+            return Err(AppError::Forbidden(
+                "that invite has already voted, change the vote instead".into(),
+            ));
+        }
+        tx.execute(
+            "UPDATE invites SET edit_token = ?1 WHERE poll_id = ?2 AND token = ?3",
+            params![token.as_str(), poll.poll.id.0, invite.as_str()],
+        )?;
+    }
+    let votes = insert_ballot(&tx, poll, vote, &token)?;
+    tx.commit()?;
+    Ok((token, votes))
+}
+
+/// Swaps the ballot cast with `token` for `vote`, returning the rows removed and added.
+pub fn replace_vote(
+    conn: &mut Connection,
+    poll: &Poll,
+    token: &EditToken,
+    vote: &VoteSubmission,
+) -> Result<(Vec<VoteId>, Vec<Vote>), AppError> {
+    let tx = conn.transaction()?;
+    let removed = delete_ballot(&tx, poll, token)?;
+    let votes = insert_ballot(&tx, poll, vote, token)?;
+    tx.commit()?;
+    Ok((removed, votes))
+}
+
+/// Removes the ballot cast with `token`, returning the rows it was.
+pub fn delete_vote(
+    conn: &mut Connection,
+    poll: &Poll,
+    token: &EditToken,
+) -> Result<Vec<VoteId>, AppError> {
+    let tx = conn.transaction()?;
+    let removed = delete_ballot(&tx, poll, token)?;
+    tx.commit()?;
+    Ok(removed)
+}
+
+fn insert_ballot(
+    conn: &Connection,
+    poll: &Poll,
+    vote: &VoteSubmission,
+    token: &EditToken,
+) -> Result<Vec<Vote>, AppError> {
+    // Anonymous ballots keep neither the voter's name nor when they voted
+    let (voter, created_at) = if poll.poll.anonymous {
+        (anonymous_label(), poll.poll.created_at)
+    } else if poll.poll.invite_only {
+        (invitee(conn, poll, token)?, now())
+    } else {
+        (vote.voter.trim().to_owned(), now())
+    };
+    let mut votes: Vec<Vote> = Vec::new();
+    for (choice_id, dots) in vote.choices.iter().filter(|(_, dots)| **dots != 0) {
+        let insert = |id: Option<i32>| {
+            conn.execute(
+                "INSERT INTO votes (id, poll_id, choice_id, dots, voter, edit_token, created_at) VALUES (COALESCE(?1, (SELECT MAX(IFNULL(MAX(id), 0), 0) + 1 FROM votes)), ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![id, poll.poll.id.0, choice_id.0, dots, voter, token.as_str(), created_at.to_string()],
+            )
+        };
+        // Sequential row ids, and the ballot ids and ordering that come from them, would give
+        // away the order anonymous ballots were cast in. Random ones are kept below zero, and
+        // everyone else's carry on counting up from above zero.
+        if poll.poll.anonymous {
+            while let Err(error) = insert(Some(anonymous_vote_id())) {
+                if error.sqlite_error_code() != Some(ErrorCode::ConstraintViolation) {
+                    return Err(error.into());
+                }
+            }
+        } else {
+            insert(None)?;
+        }
+        votes.push(Vote {
+            id: VoteId(conn.last_insert_rowid() as i32),
+            poll_id: poll.poll.id,
+            choice_id: *choice_id,
+            dots: *dots,
+            voter: voter.clone(),
+            ballot: BallotId::default(),
+            created_at,
+        });
+    }
+    // Matches what `find_results` works out, see `BALLOT`
+    if let Some(ballot) = votes.iter().map(|vote| vote.id.0).min() {
+        for vote in &mut votes {
+            vote.ballot = BallotId(ballot);
+        }
+    }
+    Ok(votes)
+}
+
+/// The name of whoever's invite cast the ballot with `token`.
+fn invitee(conn: &Connection, poll: &Poll, token: &EditToken) -> Result<String, AppError> {
+    conn.query_row(
+        "SELECT name FROM invites WHERE poll_id = ?1 AND edit_token = ?2",
+        params![poll.poll.id.0, token.as_str()],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(invite_mismatch)
+}
+
+/// A fresh label for an anonymous ballot. It's random so it doesn't give away the order ballots
+/// were cast in.
+fn anonymous_label() -> String {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    format!("Anonymous {}", &id[..8])
+}
+
+/// A random row id for a vote on an anonymous poll, from `i32::MIN` to -1.
+fn anonymous_vote_id() -> i32 {
+    -1 - (uuid::Uuid::new_v4().as_u128() as u32 >> 1) as i32
+}
+
+fn delete_ballot(
+    conn: &Connection,
+    poll: &Poll,
+    token: &EditToken,
+) -> Result<Vec<VoteId>, AppError> {
+    let mut stmt =
+        conn.prepare("DELETE FROM votes WHERE poll_id = ?1 AND edit_token = ?2 RETURNING id")?;
+    let removed: Vec<VoteId> = stmt
+        .query_map(params![poll.poll.id.0, token.as_str()], |row| {
+            Ok(VoteId(row.get(0)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    if removed.is_empty() {
+        return Err(AppError::Forbidden(
+            "no ballot on this poll matches that edit token".into(),
+        ));
+    }
+    Ok(removed)
+}
+
+/// A vote's ballot id: the lowest row id cast with the same edit token. Ballots from before edit
+/// tokens are told apart by voter and time, as in `delete_ballot_of`.
+const BALLOT: &str = "(SELECT MIN(ballot.id) FROM votes AS ballot WHERE ballot.poll_id = votes.poll_id AND (ballot.edit_token = votes.edit_token OR (ballot.edit_token IS NULL AND votes.edit_token IS NULL AND ballot.voter = votes.voter AND ballot.created_at = votes.created_at)))";
+
+pub fn find_results(conn: &Connection, uuid: &PollUuid) -> Result<PollResults, AppError> {
+    let Poll { poll, choices } = find_poll(conn, uuid)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, poll_id, choice_id, dots, voter, created_at, {} FROM votes WHERE poll_id = ?1 ORDER BY id",
+        BALLOT
+    ))?;
+    let votes = stmt
+        .query_map(params![poll.id.0], |row| {
+            Ok(Vote {
+                id: VoteId(row.get(0)?),
+                poll_id: PollId(row.get(1)?),
+                choice_id: ChoiceId(row.get(2)?),
+                dots: row.get(3)?,
+                voter: row.get(4)?,
+                created_at: timestamp_column(row, 5)?,
+                ballot: BallotId(row.get(6)?),
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    let participation = find_participation(conn, &poll)?;
+    Ok(PollResults {
+        poll,
+        choices,
+        votes,
+        participation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn open() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", true).unwrap();
+        migrate(&mut conn).unwrap();
+        conn
+    }
+
+    /// A three choice dot voting poll with five dots each, plus whatever's in `extra`.
+    fn create(extra: serde_json::Value) -> CreatePoll {
+        let mut create = json!({
+            "title": "Lunch",
+            "choices": ["Pizza", "Tacos", "Ramen"],
+            "dot_budget": {"type": "fixed", "dots": 5},
+            "rules": {},
+        });
+        if let (Some(create), Some(extra)) = (create.as_object_mut(), extra.as_object()) {
+            create.extend(extra.clone());
+        }
+        serde_json::from_value(create).unwrap()
+    }
+
+    fn submission(poll: &Poll, voter: &str, dots: &[i32]) -> VoteSubmission {
+        VoteSubmission {
+            voter: voter.into(),
+            choices: poll
+                .choices
+                .iter()
+                .zip(dots)
+                .map(|(choice, dots)| (choice.id, *dots))
+                .collect::<HashMap<_, _>>(),
+            invite: None,
+        }
+    }
+
+    fn row(vote: &Vote) -> (VoteId, ChoiceId, i32, &str, BallotId) {
+        (
+            vote.id,
+            vote.choice_id,
+            vote.dots,
+            vote.voter.as_str(),
+            vote.ballot,
+        )
+    }
+
+    fn user_version(conn: &Connection) -> usize {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrations_apply_in_order() {
+        let mut conn = open();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        // Running again is a no-op rather than re-adding columns
+        migrate(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn migrations_pick_up_where_an_old_database_stopped() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute(
+            "INSERT INTO polls (uuid, title, dot_budget, rules, created_at) VALUES ('old', 'Old', ?1, '{}', ?2)",
+            params![r#"{"type":"fixed","dots":3}"#, now().to_string()],
+        )
+        .unwrap();
+        migrate(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        let poll = find_poll(&conn, &PollUuid("old".into())).unwrap();
+        assert_eq!(poll.poll.title, "Old");
+        assert_eq!(poll.poll.results_visibility, Default::default());
+        assert!(!poll.poll.anonymous);
+        assert!(!poll.poll.invite_only);
+    }
+
+    #[test]
+    fn created_polls_round_trip() {
+        let mut conn = open();
+        let create = create(json!({"rules": {"max_per_choice": 3}, "roster": ["Ana", " ", "Bo"]}));
+        let (uuid, admin_token, invites) = insert_poll(&mut conn, &create).unwrap();
+        let poll = find_poll(&conn, &uuid).unwrap();
+        assert_eq!(poll.poll.uuid, uuid);
+        assert_eq!(poll.poll.title, "Lunch");
+        assert_eq!(poll.poll.dot_budget, create.dot_budget);
+        assert_eq!(poll.poll.rules, create.rules);
+        assert!(poll.poll.invite_only);
+        let details: Vec<&str> = poll
+            .choices
+            .iter()
+            .map(|choice| choice.details.as_str())
+            .collect();
+        assert_eq!(details, ["Pizza", "Tacos", "Ramen"]);
+        let names: Vec<&str> = invites.iter().map(|invite| invite.name.as_str()).collect();
+        assert_eq!(names, ["Ana", "Bo"]);
+        assert_eq!(
+            find_admin_token(&conn, &uuid).unwrap(),
+            Some(admin_token.as_str().to_owned())
+        );
+        assert!(find_results(&conn, &uuid).unwrap().votes.is_empty());
+        assert!(matches!(
+            find_poll(&conn, &PollUuid("missing".into())),
+            Err(AppError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn invalid_votes_are_rejected() {
+        let mut conn = open();
+        let (uuid, _, _) = insert_poll(&mut conn, &create(json!({}))).unwrap();
+        let poll = find_poll(&conn, &uuid).unwrap();
+        assert!(poll
+            .validate_vote(&submission(&poll, "Ana", &[2, 2, 1]))
+            .is_empty());
+        let problems = poll.validate_vote(&submission(&poll, "Ana", &[4, 4, 0]));
+        assert!(problems.contains_key("choices"));
+        let problems = poll.validate_vote(&submission(&poll, " ", &[1, 0, 0]));
+        assert!(problems.contains_key("voter"));
+    }
+
+    #[test]
+    fn invite_only_polls_need_an_unused_invite() {
+        let mut conn = open();
+        let (uuid, _, invites) =
+            insert_poll(&mut conn, &create(json!({"roster": ["Ana"]}))).unwrap();
+        let poll = find_poll(&conn, &uuid).unwrap();
+        let mut vote = submission(&poll, "Someone else", &[5, 0, 0]);
+        assert!(matches!(
+            insert_vote(&mut conn, &poll, &vote),
+            Err(AppError::Forbidden(_))
+        ));
+        vote.invite = Some(invites[0].token.clone());
+        let (_, votes) = insert_vote(&mut conn, &poll, &vote).unwrap();
+        assert_eq!(votes[0].voter, "Ana");
+        assert!(matches!(
+            insert_vote(&mut conn, &poll, &vote),
+            Err(AppError::Forbidden(_))
+        ));
+        assert_eq!(find_results(&conn, &uuid).unwrap().votes.len(), 1);
+    }
+
+    #[test]
+    fn edit_tokens_change_and_withdraw_ballots() {
+        let mut conn = open();
+        let (uuid, _, _) = insert_poll(&mut conn, &create(json!({}))).unwrap();
+        let poll = find_poll(&conn, &uuid).unwrap();
+        let (token, first) =
+            insert_vote(&mut conn, &poll, &submission(&poll, "Ana", &[3, 2, 0])).unwrap();
+        let (_, other) =
+            insert_vote(&mut conn, &poll, &submission(&poll, "Bo", &[0, 0, 5])).unwrap();
+        assert_eq!(find_results(&conn, &uuid).unwrap().votes.len(), 3);
+
+        let (mut removed, changed) = replace_vote(
+            &mut conn,
+            &poll,
+            &token,
+            &submission(&poll, "Ana", &[0, 1, 4]),
+        )
+        .unwrap();
+        let mut first_ids: Vec<VoteId> = first.iter().map(|vote| vote.id).collect();
+        first_ids.sort();
+        removed.sort();
+        assert_eq!(removed, first_ids);
+        let votes = find_results(&conn, &uuid).unwrap().votes;
+        assert_eq!(votes.len(), 3);
+        // What's handed back matches what a fresh fetch sees, ballots included. Stored times
+        // are only kept to the millisecond, so leave them out.
+        let rows: Vec<_> = votes.iter().map(row).collect();
+        assert!(changed
+            .iter()
+            .chain(&other)
+            .all(|vote| rows.contains(&row(vote))));
+        assert!(changed.iter().all(|vote| vote.ballot == changed[0].ballot));
+        assert_ne!(changed[0].ballot, other[0].ballot);
+
+        let withdrawn = delete_vote(&mut conn, &poll, &token).unwrap();
+        assert_eq!(withdrawn.len(), changed.len());
+        let votes = find_results(&conn, &uuid).unwrap().votes;
+        assert_eq!(
+            votes.iter().map(row).collect::<Vec<_>>(),
+            other.iter().map(row).collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            delete_vote(&mut conn, &poll, &token),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            replace_vote(
+                &mut conn,
+                &poll,
+                &EditToken("made up".into()),
+                &submission(&poll, "Ana", &[5, 0, 0])
+            ),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn anonymous_ballots_dont_give_away_their_order() {
+        let mut conn = open();
+        let (uuid, _, _) = insert_poll(&mut conn, &create(json!({"anonymous": true}))).unwrap();
+        let poll = find_poll(&conn, &uuid).unwrap();
+        let mut cast = Vec::new();
+        for voter in ["Ana", "Bo", "Cy"] {
+            let (_, votes) =
+                insert_vote(&mut conn, &poll, &submission(&poll, voter, &[3, 2, 0])).unwrap();
+            cast.extend(votes);
+        }
+        assert!(cast.iter().all(|vote| vote.id.0 < 0));
+        assert!(cast.iter().all(|vote| vote.voter.starts_with("Anonymous ")));
+        assert!(cast
+            .iter()
+            .all(|vote| vote.created_at == poll.poll.created_at));
+        let votes = find_results(&conn, &uuid).unwrap().votes;
+        let rows: Vec<_> = votes.iter().map(row).collect();
+        assert!(cast.iter().all(|vote| rows.contains(&row(vote))));
+
+        // Row ids are still unique, so the creator can take down a single ballot
+        let removed = delete_ballot_of(&mut conn, &poll, cast[0].id).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(find_results(&conn, &uuid).unwrap().votes.len(), 4);
+        // Everyone else's ids still count up from 1
+        let (uuid, _, _) = insert_poll(&mut conn, &create(json!({}))).unwrap();
+        let poll = find_poll(&conn, &uuid).unwrap();
+        let (_, votes) =
+            insert_vote(&mut conn, &poll, &submission(&poll, "Di", &[5, 0, 0])).unwrap();
+        assert_eq!(votes[0].id, VoteId(1));
+    }
+}
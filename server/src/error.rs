@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use dotdotvote_types::v1::ErrorBody;
+
+/// Everything a handler can fail with, rendered as the error body the frontend's `ApiError`
+/// decodes.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound,
+    /// The request needs a token it wasn't given, or one that doesn't match.
+    Forbidden(String),
+    Validation(BTreeMap<String, Vec<String>>),
+    Internal(String),
+}
+
+impl AppError {
+    /// A validation error with a single problem.
+    pub fn invalid(field: &str, message: &str) -> Self {
+        let mut fields = BTreeMap::new();
+        fields.insert(field.to_owned(), vec![message.to_owned()]);
+        AppError::Validation(fields)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, message, errors) = match self {
+            AppError::NotFound => (
+                StatusCode::NOT_FOUND,
+                Some("poll not found".to_owned()),
+                BTreeMap::new(),
+            ),
+            AppError::Forbidden(message) => (StatusCode::FORBIDDEN, Some(message), BTreeMap::new()),
+            AppError::Validation(errors) => (StatusCode::UNPROCESSABLE_ENTITY, None, errors),
+            AppError::Internal(message) => {
+                eprintln!("internal error: {}", message);
+                (StatusCode::INTERNAL_SERVER_ERROR, None, BTreeMap::new())
+            }
+        };
+        (status, Json(ErrorBody { message, errors })).into_response()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(error: rusqlite::Error) -> Self {
+        match error {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound,
+            error => AppError::Internal(error.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::Internal(error.to_string())
+    }
+}
@@ -0,0 +1,29 @@
+//! A self-hostable implementation of the dotdotvote API, backed by SQLite.
+//!
+//! Configured through the environment:
+//!
+//! - `DOTDOTVOTE_ADDR`: where to listen, defaults to `127.0.0.1:8000`
+//! - `DOTDOTVOTE_DATABASE`: the SQLite database file, defaults to `dotdotvote.db`
+use std::env;
+
+use tower_http::cors::CorsLayer;
+
+mod db;
+mod error;
+mod routes;
+
+#[tokio::main]
+async fn main() {
+    let addr = env::var("DOTDOTVOTE_ADDR").unwrap_or_else(|_| "127.0.0.1:8000".into());
+    let database = env::var("DOTDOTVOTE_DATABASE").unwrap_or_else(|_| "dotdotvote.db".into());
+
+    let db = db::Db::open(&database).expect("failed to open database");
+    // The frontend is usually served from a different origin
+    let app = routes::router(db).layer(CorsLayer::permissive());
+
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .expect("failed to bind address");
+    println!("dotdotvote listening on http://{}", addr);
+    axum::serve(listener, app).await.expect("server error");
+}
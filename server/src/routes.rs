@@ -0,0 +1,697 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::extract::{FromRef, Path, Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use dotdotvote_types::v1::{
+    AdvancePhase, CreatePoll, CreatePollResponse, Invite, NewChoice, Participant, Poll, PollEvent,
+    PollMetadata, PollPhase, PollResults, PollStatus, ResultsViewer, UpdatePoll, VoteReceipt,
+    VoteSubmission, PREFIX,
+};
+use dotdotvote_types::{AdminToken, EditToken, InviteToken, PollId, PollUuid, VoteId};
+use serde::Deserialize;
+use tokio::sync::broadcast;
+use tokio::time::sleep;
+use tokio_stream::Stream;
+
+use crate::db::{self, Db};
+use crate::error::AppError;
+
+/// An edit or admin token sent along to see results that are hidden from everyone else.
+#[derive(Deserialize)]
+struct ViewerToken {
+    token: Option<String>,
+}
+
+/// Fans poll events out to every client watching that poll.
+pub type Events = broadcast::Sender<(PollId, PollEvent)>;
+
+#[derive(Clone)]
+struct AppState {
+    db: Db,
+    events: Events,
+}
+
+impl FromRef<AppState> for Db {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for Events {
+    fn from_ref(state: &AppState) -> Self {
+        state.events.clone()
+    }
+}
+
+pub fn router(db: Db) -> Router {
+    let (events, _) = broadcast::channel(256);
+    let api = Router::new()
+        .route("/polls", put(create_poll))
+        .route("/polls/{id}", get(get_poll))
+        .route("/polls/{id}/choices", post(add_choice))
+        .route("/polls/{id}/invites/{token}", get(get_invite))
+        .route("/polls/{id}/vote", post(vote))
+        .route(
+            "/polls/{id}/vote/{token}",
+            put(change_vote).delete(withdraw_vote),
+        )
+        .route("/polls/{id}/results", get(get_results))
+        .route(
+            "/polls/{id}/admin/{token}",
+            get(get_admin).patch(update_poll).delete(delete_poll),
+        )
+        .route("/polls/{id}/admin/{token}/close", post(close_poll))
+        .route("/polls/{id}/admin/{token}/reopen", post(reopen_poll))
+        .route("/polls/{id}/admin/{token}/phase", post(advance_phase))
+        .route("/polls/{id}/admin/{token}/invites", get(get_invites))
+        .route(
+            "/polls/{id}/admin/{token}/votes/{vote}",
+            delete(delete_ballot),
+        )
+        .route("/polls/{id}/events", get(watch_poll))
+        .with_state(AppState { db, events });
+    Router::new().nest(PREFIX, api)
+}
+
+async fn create_poll(
+    State(db): State<Db>,
+    Json(create): Json<CreatePoll>,
+) -> Result<Json<CreatePollResponse>, AppError> {
+    ensure_valid(create.validate(db::now()))?;
+    let (poll, admin_token, invites) = db.call(move |conn| db::insert_poll(conn, &create)).await?;
+    Ok(Json(CreatePollResponse {
+        poll,
+        admin_token: Some(admin_token),
+        invites,
+    }))
+}
+
+async fn get_poll(State(db): State<Db>, Path(id): Path<PollUuid>) -> Result<Json<Poll>, AppError> {
+    let poll = db.call(move |conn| db::find_poll(conn, &id)).await?;
+    Ok(Json(poll))
+}
+
+/// Who an invite link is for, so their ballot can be filled in with their name.
+async fn get_invite(
+    State(db): State<Db>,
+    Path((id, token)): Path<(PollUuid, InviteToken)>,
+) -> Result<Json<Participant>, AppError> {
+    let participant = db
+        .call(move |conn| {
+            let poll = db::find_poll(conn, &id)?;
+            db::find_invite(conn, &poll, &token)
+        })
+        .await?;
+    Ok(Json(participant))
+}
+
+async fn vote(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path(id): Path<PollUuid>,
+    Json(vote): Json<VoteSubmission>,
+) -> Result<Json<VoteReceipt>, AppError> {
+    let (poll_id, edit_token, votes) = db
+        .call(move |conn| {
+            let poll = db::find_poll(conn, &id)?;
+            ensure_open(&poll)?;
+            ensure_valid(poll.validate_vote(&vote))?;
+            let (edit_token, votes) = db::insert_vote(conn, &poll, &vote)?;
+            Ok((poll.poll.id, edit_token, votes))
+        })
+        .await?;
+    // Nobody watching isn't an error
+    let _ = events.send((poll_id, PollEvent::Voted { votes }));
+    Ok(Json(VoteReceipt { edit_token }))
+}
+
+async fn change_vote(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path((id, token)): Path<(PollUuid, EditToken)>,
+    Json(vote): Json<VoteSubmission>,
+) -> Result<(), AppError> {
+    let (poll_id, removed, votes) = db
+        .call(move |conn| {
+            let poll = db::find_poll(conn, &id)?;
+            ensure_open(&poll)?;
+            ensure_valid(poll.validate_vote(&vote))?;
+            let (removed, votes) = db::replace_vote(conn, &poll, &token, &vote)?;
+            Ok((poll.poll.id, removed, votes))
+        })
+        .await?;
+    let _ = events.send((poll_id, PollEvent::Withdrawn { votes: removed }));
+    let _ = events.send((poll_id, PollEvent::Voted { votes }));
+    Ok(())
+}
+
+async fn withdraw_vote(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path((id, token)): Path<(PollUuid, EditToken)>,
+) -> Result<(), AppError> {
+    let (poll_id, removed) = db
+        .call(move |conn| {
+            let poll = db::find_poll(conn, &id)?;
+            ensure_open(&poll)?;
+            Ok((poll.poll.id, db::delete_vote(conn, &poll, &token)?))
+        })
+        .await?;
+    let _ = events.send((poll_id, PollEvent::Withdrawn { votes: removed }));
+    Ok(())
+}
+
+/// Votes can only be cast, changed or withdrawn while the poll is open, and brainstorming polls
+/// only take them in their voting phase.
+fn ensure_open(poll: &Poll) -> Result<(), AppError> {
+    match poll.poll.status(db::now()) {
+        PollStatus::Scheduled => Err(AppError::Forbidden("voting hasn't opened yet".into())),
+        PollStatus::Closed => Err(AppError::Forbidden("voting has closed".into())),
+        PollStatus::Open if poll.poll.collecting() => Err(AppError::Forbidden(
+            "choices are still being collected".into(),
+        )),
+        PollStatus::Open => Ok(()),
+    }
+}
+
+async fn add_choice(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path(id): Path<PollUuid>,
+    Json(new): Json<NewChoice>,
+) -> Result<Json<Poll>, AppError> {
+    let poll = db
+        .call(move |conn| {
+            let poll = db::find_poll(conn, &id)?;
+            if !poll.poll.collecting() {
+                return Err(AppError::Forbidden(
+                    "this poll isn't collecting choices".into(),
+                ));
+            }
+            ensure_valid(poll.validate_new_choice(&new))?;
+            db::insert_choice(conn, &poll, &new)
+        })
+        .await?;
+    let _ = events.send((poll.poll.id, PollEvent::Updated { poll: poll.clone() }));
+    Ok(Json(poll))
+}
+
+/// Turns the problems a check found into an error, if there were any.
+fn ensure_valid(problems: BTreeMap<String, Vec<String>>) -> Result<(), AppError> {
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(problems))
+    }
+}
+
+/// The results with everything a creator can manage.
+async fn get_admin(
+    State(db): State<Db>,
+    Path((id, token)): Path<(PollUuid, AdminToken)>,
+) -> Result<Json<PollResults>, AppError> {
+    let results = db
+        .call(move |conn| {
+            db::find_admin_poll(conn, &id, &token)?;
+            db::find_results(conn, &id)
+        })
+        .await?;
+    Ok(Json(results))
+}
+
+/// The roster's invite links, for the creator to hand out again.
+async fn get_invites(
+    State(db): State<Db>,
+    Path((id, token)): Path<(PollUuid, AdminToken)>,
+) -> Result<Json<Vec<Invite>>, AppError> {
+    let invites = db
+        .call(move |conn| {
+            let poll = db::find_admin_poll(conn, &id, &token)?;
+            db::find_invites(conn, &poll)
+        })
+        .await?;
+    Ok(Json(invites))
+}
+
+async fn update_poll(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path((id, token)): Path<(PollUuid, AdminToken)>,
+    Json(update): Json<UpdatePoll>,
+) -> Result<Json<Poll>, AppError> {
+    let poll = db
+        .call(move |conn| {
+            let poll = db::find_admin_poll(conn, &id, &token)?;
+            ensure_valid(poll.validate_update(&update))?;
+            db::update_poll(conn, &poll, &update)
+        })
+        .await?;
+    let _ = events.send((poll.poll.id, PollEvent::Updated { poll: poll.clone() }));
+    Ok(Json(poll))
+}
+
+async fn close_poll(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path((id, token)): Path<(PollUuid, AdminToken)>,
+) -> Result<Json<Poll>, AppError> {
+    let poll = db
+        .call(move |conn| {
+            let mut poll = db::find_admin_poll(conn, &id, &token)?;
+            if poll.poll.status(db::now()) != PollStatus::Closed {
+                db::close_poll(conn, &mut poll)?;
+            }
+            Ok(poll)
+        })
+        .await?;
+    let _ = events.send((poll.poll.id, PollEvent::Updated { poll: poll.clone() }));
+    Ok(Json(poll))
+}
+
+async fn reopen_poll(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path((id, token)): Path<(PollUuid, AdminToken)>,
+) -> Result<Json<Poll>, AppError> {
+    let poll = db
+        .call(move |conn| {
+            let mut poll = db::find_admin_poll(conn, &id, &token)?;
+            if poll.poll.phase == Some(PollPhase::Reveal) {
+                return Err(AppError::invalid(
+                    "phase",
+                    "polls can't be reopened once their results are revealed",
+                ));
+            }
+            if poll.poll.status(db::now()) == PollStatus::Closed {
+                db::reopen_poll(conn, &mut poll)?;
+            }
+            Ok(poll)
+        })
+        .await?;
+    let _ = events.send((poll.poll.id, PollEvent::Updated { poll: poll.clone() }));
+    Ok(Json(poll))
+}
+
+async fn advance_phase(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path((id, token)): Path<(PollUuid, AdminToken)>,
+    Json(advance): Json<AdvancePhase>,
+) -> Result<Json<Poll>, AppError> {
+    let poll = db
+        .call(move |conn| {
+            let poll = db::find_admin_poll(conn, &id, &token)?;
+            ensure_valid(poll.validate_advance(&advance))?;
+            db::advance_phase(conn, &poll, &advance)
+        })
+        .await?;
+    let _ = events.send((poll.poll.id, PollEvent::Updated { poll: poll.clone() }));
+    Ok(Json(poll))
+}
+
+async fn delete_ballot(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path((id, token, vote)): Path<(PollUuid, AdminToken, VoteId)>,
+) -> Result<(), AppError> {
+    let (poll_id, removed) = db
+        .call(move |conn| {
+            let poll = db::find_admin_poll(conn, &id, &token)?;
+            Ok((poll.poll.id, db::delete_ballot_of(conn, &poll, vote)?))
+        })
+        .await?;
+    let _ = events.send((poll_id, PollEvent::Withdrawn { votes: removed }));
+    Ok(())
+}
+
+async fn delete_poll(
+    State(db): State<Db>,
+    Path((id, token)): Path<(PollUuid, AdminToken)>,
+) -> Result<(), AppError> {
+    db.call(move |conn| {
+        let poll = db::find_admin_poll(conn, &id, &token)?;
+        db::delete_poll(conn, &poll)
+    })
+    .await
+}
+
+async fn get_results(
+    State(db): State<Db>,
+    Path(id): Path<PollUuid>,
+    Query(viewer): Query<ViewerToken>,
+) -> Result<Json<PollResults>, AppError> {
+    let (results, viewer) = db
+        .call(move |conn| {
+            let results = db::find_results(conn, &id)?;
+            let viewer = db::find_viewer(conn, &results.poll, viewer.token.as_deref())?;
+            Ok((results, viewer))
+        })
+        .await?;
+    if !results.poll.results_visible_to(viewer, db::now()) {
+        return Err(AppError::Forbidden(
+            results.poll.results_hidden_reason().into(),
+        ));
+    }
+    Ok(Json(results))
+}
+
+async fn watch_poll(
+    State(db): State<Db>,
+    State(events): State<Events>,
+    Path(id): Path<PollUuid>,
+    Query(viewer): Query<ViewerToken>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let (poll, viewer) = db
+        .call(move |conn| {
+            let poll = db::find_poll(conn, &id)?;
+            let viewer = db::find_viewer(conn, &poll.poll, viewer.token.as_deref())?;
+            Ok((poll, viewer))
+        })
+        .await?;
+    let watch = Watch {
+        db,
+        events: events.subscribe(),
+        viewer,
+        poll: poll.poll,
+    };
+    let stream = futures::stream::unfold(watch, |mut watch| async move {
+        let event = watch.next().await?;
+        Some((Ok(event), watch))
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// One viewer's subscription to a poll's events.
+struct Watch {
+    db: Db,
+    events: broadcast::Receiver<(PollId, PollEvent)>,
+    viewer: ResultsViewer,
+    // The poll as of the latest `Updated` event, which decides what the viewer may see
+    poll: PollMetadata,
+}
+
+impl Watch {
+    /// The next event for the viewer, or `None` once there won't be any more.
+    async fn next(&mut self) -> Option<Event> {
+        loop {
+            let event = tokio::select! {
+                received = self.events.recv() => match received {
+                    Ok((id, event)) if id == self.poll.id => event,
+                    // A client that falls too far behind misses events rather than holding
+                    // everyone up
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                // Closing on time sends no event, so tell watchers then, for any hidden results
+                // to show. The poll may have changed since, so it's read afresh.
+                () = until_closed(&self.poll) => {
+                    let uuid = self.poll.uuid.clone();
+                    let poll = self.db.call(move |conn| db::find_poll(conn, &uuid)).await.ok()?;
+                    PollEvent::Updated { poll }
+                }
+            };
+            match &event {
+                PollEvent::Updated { poll } => self.poll = poll.poll.clone(),
+                // Votes stay hidden from viewers who can't see the results right now
+                PollEvent::Voted { .. } | PollEvent::Withdrawn { .. }
+                    if !self.poll.results_visible_to(self.viewer, db::now()) =>
+                {
+                    continue
+                }
+                _ => {}
+            }
+            if let Ok(event) = Event::default().json_data(&event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+/// Waits until `poll` closes, or forever if it never will or already has.
+async fn until_closed(poll: &PollMetadata) {
+    match poll
+        .closes_at
+        .map(|closes_at| closes_at.millis() - db::now().millis())
+    {
+        Some(wait) if wait > 0 => sleep(Duration::from_millis(wait as u64)).await,
+        _ => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Method, Request, StatusCode};
+    use dotdotvote_types::Timestamp;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app() -> Router {
+        router(Db::open(":memory:").unwrap())
+    }
+
+    /// Sends `body`, if any, to `path` under the API prefix and decodes what comes back.
+    async fn send(
+        app: &Router,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(format!("{}{}", PREFIX, path))
+            .header("content-type", "application/json");
+        let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+        let response = app
+            .clone()
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).unwrap()
+        };
+        (status, body)
+    }
+
+    /// Creates a three choice dot voting poll with three dots each, plus whatever's in `extra`,
+    /// returning the poll and its admin token.
+    async fn create(app: &Router, extra: Value) -> (Poll, String) {
+        let mut create = json!({
+            "title": "Lunch",
+            "choices": ["Pizza", "Tacos", "Ramen"],
+            "dot_budget": {"type": "fixed", "dots": 3},
+            "rules": {},
+        });
+        if let (Some(create), Some(extra)) = (create.as_object_mut(), extra.as_object()) {
+            create.extend(extra.clone());
+        }
+        let (status, created) = send(app, Method::PUT, "/polls", Some(create)).await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        let created: CreatePollResponse = serde_json::from_value(created).unwrap();
+        let (_, poll) = send(
+            app,
+            Method::GET,
+            &format!("/polls/{}", created.poll.0),
+            None,
+        )
+        .await;
+        let token = created.admin_token.unwrap();
+        (
+            serde_json::from_value(poll).unwrap(),
+            token.as_str().to_owned(),
+        )
+    }
+
+    fn submission(poll: &Poll, voter: &str, dots: &[i32]) -> Value {
+        let vote = VoteSubmission {
+            voter: voter.into(),
+            choices: poll
+                .choices
+                .iter()
+                .zip(dots)
+                .map(|(choice, dots)| (choice.id, *dots))
+                .collect::<HashMap<_, _>>(),
+            invite: None,
+        };
+        serde_json::to_value(vote).unwrap()
+    }
+
+    async fn vote(app: &Router, poll: &Poll, voter: &str, dots: &[i32]) -> (StatusCode, Value) {
+        let path = format!("/polls/{}/vote", poll.poll.uuid.0);
+        send(
+            app,
+            Method::POST,
+            &path,
+            Some(submission(poll, voter, dots)),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn votes_show_up_in_the_results() {
+        let app = app();
+        let (poll, _) = create(&app, json!({})).await;
+        let (status, receipt) = vote(&app, &poll, "Ana", &[2, 1, 0]).await;
+        assert_eq!(status, StatusCode::OK);
+        let receipt: VoteReceipt = serde_json::from_value(receipt).unwrap();
+        let (status, _) = vote(&app, &poll, "Bo", &[0, 0, 3]).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let results_path = format!("/polls/{}/results", poll.poll.uuid.0);
+        let (status, results) = send(&app, Method::GET, &results_path, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let results: PollResults = serde_json::from_value(results).unwrap();
+        let mut dots: Vec<_> = results
+            .votes
+            .iter()
+            .map(|vote| (vote.voter.as_str(), vote.dots))
+            .collect();
+        dots.sort();
+        assert_eq!(dots, [("Ana", 1), ("Ana", 2), ("Bo", 3)]);
+
+        let change = format!(
+            "/polls/{}/vote/{}",
+            poll.poll.uuid.0,
+            receipt.edit_token.as_str()
+        );
+        let (status, _) = send(
+            &app,
+            Method::PUT,
+            &change,
+            Some(submission(&poll, "Ana", &[0, 3, 0])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&app, Method::DELETE, &change, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, results) = send(&app, Method::GET, &results_path, None).await;
+        let results: PollResults = serde_json::from_value(results).unwrap();
+        assert!(results.votes.iter().all(|vote| vote.voter == "Bo"));
+    }
+
+    #[tokio::test]
+    async fn bad_requests_are_turned_away() {
+        let app = app();
+        let (status, body) = send(
+            &app,
+            Method::PUT,
+            "/polls",
+            Some(json!({
+                "title": " ",
+                "choices": ["Pizza"],
+                "dot_budget": {"type": "fixed", "dots": 3},
+                "rules": {},
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["errors"]["title"].is_array());
+        assert!(body["errors"]["choices"].is_array());
+
+        let (poll, _) = create(&app, json!({})).await;
+        let (status, body) = vote(&app, &poll, "Ana", &[3, 3, 0]).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["errors"]["choices"].is_array());
+        let (status, _) = send(&app, Method::GET, "/polls/missing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let wrong = format!("/polls/{}/admin/made-up/close", poll.poll.uuid.0);
+        let (status, _) = send(&app, Method::POST, &wrong, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn hidden_results_are_forbidden() {
+        let app = app();
+        let (poll, admin) = create(&app, json!({"results_visibility": "after_vote"})).await;
+        let results = format!("/polls/{}/results", poll.poll.uuid.0);
+        let (status, body) = send(&app, Method::GET, &results, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body["message"].is_string());
+
+        // Voters and the creator can see them
+        let (_, receipt) = vote(&app, &poll, "Ana", &[3, 0, 0]).await;
+        let receipt: VoteReceipt = serde_json::from_value(receipt).unwrap();
+        let as_voter = format!("{}?token={}", results, receipt.edit_token.as_str());
+        assert_eq!(
+            send(&app, Method::GET, &as_voter, None).await.0,
+            StatusCode::OK
+        );
+        let as_admin = format!("{}?token={}", results, admin);
+        assert_eq!(
+            send(&app, Method::GET, &as_admin, None).await.0,
+            StatusCode::OK
+        );
+        let as_stranger = format!("{}?token=made-up", results);
+        assert_eq!(
+            send(&app, Method::GET, &as_stranger, None).await.0,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn votes_wait_for_the_poll_to_open() {
+        let app = app();
+        let (poll, admin) = create(&app, json!({})).await;
+        let close = format!("/polls/{}/admin/{}/close", poll.poll.uuid.0, admin);
+        assert_eq!(
+            send(&app, Method::POST, &close, None).await.0,
+            StatusCode::OK
+        );
+        assert_eq!(
+            vote(&app, &poll, "Ana", &[3, 0, 0]).await.0,
+            StatusCode::FORBIDDEN
+        );
+        let reopen = format!("/polls/{}/admin/{}/reopen", poll.poll.uuid.0, admin);
+        assert_eq!(
+            send(&app, Method::POST, &reopen, None).await.0,
+            StatusCode::OK
+        );
+        assert_eq!(vote(&app, &poll, "Ana", &[3, 0, 0]).await.0, StatusCode::OK);
+
+        let later = Timestamp::from_millis(db::now().millis() + 60 * 60 * 1000).unwrap();
+        let (scheduled, _) = create(&app, json!({"opens_at": later})).await;
+        assert_eq!(
+            vote(&app, &scheduled, "Ana", &[3, 0, 0]).await.0,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn brainstorms_only_take_votes_in_their_voting_phase() {
+        let app = app();
+        let (poll, admin) = create(&app, json!({"brainstorm": true})).await;
+        assert_eq!(poll.poll.phase, Some(PollPhase::Collect));
+        assert_eq!(
+            vote(&app, &poll, "Ana", &[3, 0, 0]).await.0,
+            StatusCode::FORBIDDEN
+        );
+
+        let phase = format!("/polls/{}/admin/{}/phase", poll.poll.uuid.0, admin);
+        let advance = json!({"phase": "vote"});
+        let (status, poll) = send(&app, Method::POST, &phase, Some(advance)).await;
+        assert_eq!(status, StatusCode::OK, "{}", poll);
+        let poll: Poll = serde_json::from_value(poll).unwrap();
+        assert_eq!(vote(&app, &poll, "Ana", &[3, 0, 0]).await.0, StatusCode::OK);
+        let choices = format!("/polls/{}/choices", poll.poll.uuid.0);
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            &choices,
+            Some(json!({"details": "Sushi"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
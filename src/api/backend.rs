@@ -0,0 +1,282 @@
+use std::rc::Rc;
+
+use futures::future::LocalBoxFuture;
+use futures::stream::LocalBoxStream;
+
+use crate::api::{
+    AdminToken, AdvancePhase, ApiClient, ApiError, CreatePoll, CreatePollResponse, EditToken,
+    Invite, InviteToken, NewChoice, Participant, Poll, PollEvent, PollResults, PollUuid,
+    UpdatePoll, VoteId, VoteReceipt, VoteSubmission,
+};
+
+/// Everything the app needs from a dotdotvote backend.
+pub trait PollBackend {
+    fn get_poll<'a>(&'a self, id: &'a PollUuid) -> LocalBoxFuture<'a, Result<Poll, ApiError>>;
+
+    fn create_poll<'a>(
+        &'a self,
+        poll: &'a CreatePoll,
+    ) -> LocalBoxFuture<'a, Result<CreatePollResponse, ApiError>>;
+
+    fn vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        vote: &'a VoteSubmission,
+    ) -> LocalBoxFuture<'a, Result<VoteReceipt, ApiError>>;
+
+    /// Replaces the ballot cast with `token`.
+    fn change_vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        token: &'a EditToken,
+        vote: &'a VoteSubmission,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>>;
+
+    /// Removes the ballot cast with `token`.
+    fn withdraw_vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        token: &'a EditToken,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>>;
+
+    /// Adds a choice to a brainstorming poll while it's collecting them.
+    fn add_choice<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        choice: &'a NewChoice,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>>;
+
+    /// Who an invite link is for, and whether they've voted.
+    fn get_invite<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a InviteToken,
+    ) -> LocalBoxFuture<'a, Result<Participant, ApiError>>;
+
+    /// A poll's results, for its creator. This and the rest of the creator's calls need the
+    /// poll's admin token.
+    fn get_admin<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<PollResults, ApiError>>;
+
+    fn update_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        update: &'a UpdatePoll,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>>;
+
+    /// The invite links for a poll's roster.
+    fn get_invites<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Vec<Invite>, ApiError>>;
+
+    /// Moves a brainstorming poll on to its next phase.
+    fn advance_phase<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        advance: &'a AdvancePhase,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>>;
+
+    /// Ends voting on a poll now.
+    fn close_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>>;
+
+    /// Opens a closed poll back up, with no closing time.
+    fn reopen_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>>;
+
+    /// Removes the ballot `vote` was cast with.
+    fn delete_ballot<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        vote: VoteId,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>>;
+
+    fn delete_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>>;
+
+    /// A poll's results. `token`, an edit or admin token, shows results hidden from others
+    /// by the poll's `ResultsVisibility`.
+    fn get_results<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: Option<&'a str>,
+    ) -> LocalBoxFuture<'a, Result<PollResults, ApiError>>;
+
+    /// Live events for a poll, with votes only where `token` can see the results. Backends
+    /// that can't push updates return `None`, and callers fall back to polling `get_results`.
+    fn watch(
+        &self,
+        _id: &PollUuid,
+        _token: Option<&str>,
+    ) -> Option<LocalBoxStream<'static, PollEvent>> {
+        None
+    }
+}
+
+impl PollBackend for ApiClient {
+    fn get_poll<'a>(&'a self, id: &'a PollUuid) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(ApiClient::get_poll(self, id))
+    }
+
+    fn create_poll<'a>(
+        &'a self,
+        poll: &'a CreatePoll,
+    ) -> LocalBoxFuture<'a, Result<CreatePollResponse, ApiError>> {
+        Box::pin(ApiClient::create_poll(self, poll))
+    }
+
+    fn vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        vote: &'a VoteSubmission,
+    ) -> LocalBoxFuture<'a, Result<VoteReceipt, ApiError>> {
+        Box::pin(ApiClient::vote(self, poll_id, vote))
+    }
+
+    fn change_vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        token: &'a EditToken,
+        vote: &'a VoteSubmission,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(ApiClient::change_vote(self, poll_id, token, vote))
+    }
+
+    fn withdraw_vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        token: &'a EditToken,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(ApiClient::withdraw_vote(self, poll_id, token))
+    }
+
+    fn add_choice<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        choice: &'a NewChoice,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(ApiClient::add_choice(self, id, choice))
+    }
+
+    fn get_invite<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a InviteToken,
+    ) -> LocalBoxFuture<'a, Result<Participant, ApiError>> {
+        Box::pin(ApiClient::get_invite(self, id, token))
+    }
+
+    fn get_admin<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<PollResults, ApiError>> {
+        Box::pin(ApiClient::get_admin(self, id, token))
+    }
+
+    fn update_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        update: &'a UpdatePoll,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(ApiClient::update_poll(self, id, token, update))
+    }
+
+    fn get_invites<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Vec<Invite>, ApiError>> {
+        Box::pin(ApiClient::get_invites(self, id, token))
+    }
+
+    fn advance_phase<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        advance: &'a AdvancePhase,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(ApiClient::advance_phase(self, id, token, advance))
+    }
+
+    fn close_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(ApiClient::close_poll(self, id, token))
+    }
+
+    fn reopen_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(ApiClient::reopen_poll(self, id, token))
+    }
+
+    fn delete_ballot<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        vote: VoteId,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(ApiClient::delete_ballot(self, id, token, vote))
+    }
+
+    fn delete_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(ApiClient::delete_poll(self, id, token))
+    }
+
+    fn get_results<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: Option<&'a str>,
+    ) -> LocalBoxFuture<'a, Result<PollResults, ApiError>> {
+        Box::pin(ApiClient::get_results(self, id, token))
+    }
+
+    fn watch(
+        &self,
+        id: &PollUuid,
+        token: Option<&str>,
+    ) -> Option<LocalBoxStream<'static, PollEvent>> {
+        ApiClient::watch(self, id, token).map(|stream| Box::pin(stream) as LocalBoxStream<_>)
+    }
+}
+
+/// The backend components should talk to: the configured API, or the browser-local mock when
+/// built with the `mock-backend` feature.
+#[cfg(not(feature = "mock-backend"))]
+pub fn backend() -> Rc<dyn PollBackend> {
+    Rc::new(ApiClient::default())
+}
+
+/// The backend components should talk to: the configured API, or the browser-local mock when
+/// built with the `mock-backend` feature.
+#[cfg(feature = "mock-backend")]
+pub fn backend() -> Rc<dyn PollBackend> {
+    Rc::new(crate::api::MockBackend::local())
+}
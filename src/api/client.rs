@@ -0,0 +1,338 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::rc::Rc;
+use std::time::Duration;
+
+use futures::future::{AbortHandle, Abortable};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use yew::prelude::*;
+
+use crate::api::{
+    AdminToken, AdvancePhase, ApiError, CreatePoll, CreatePollResponse, EditToken, EventStream,
+    FetchTransport, HttpRequest, Invite, InviteToken, NewChoice, Participant, Poll, PollResults,
+    PollUuid, Transport, UpdatePoll, VoteId, VoteReceipt, VoteSubmission, PREFIX,
+};
+use crate::config;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Talks to the dotdotvote API. Cheap to clone, clones share a transport.
+#[derive(Clone)]
+pub struct ApiClient {
+    base_url: String,
+    timeout: Option<Duration>,
+    transport: Rc<dyn Transport>,
+}
+
+impl Default for ApiClient {
+    /// A client for the configured API URL that sends requests with `fetch`.
+    fn default() -> Self {
+        ApiClient::new(config::api_url())
+    }
+}
+
+impl ApiClient {
+    pub fn new<S: Into<String>>(base_url: S) -> Self {
+        ApiClient {
+            base_url: base_url.into(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            transport: Rc::new(FetchTransport),
+        }
+    }
+
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Rc::new(transport);
+        self
+    }
+
+    /// Sets how long to wait for each response, `None` waits forever.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub async fn get_poll(&self, id: &PollUuid) -> Result<Poll, ApiError> {
+        self.request("GET", &format!("/polls/{}", segment(id)), None::<&()>)
+            .await
+    }
+
+    pub async fn create_poll(&self, poll: &CreatePoll) -> Result<CreatePollResponse, ApiError> {
+        self.request("PUT", "/polls", Some(poll)).await
+    }
+
+    pub async fn vote(
+        &self,
+        poll_id: &PollUuid,
+        vote: &VoteSubmission,
+    ) -> Result<VoteReceipt, ApiError> {
+        self.request(
+            "POST",
+            &format!("/polls/{}/vote", segment(poll_id)),
+            Some(vote),
+        )
+        .await
+    }
+
+    /// Replaces the ballot cast with `token`.
+    pub async fn change_vote(
+        &self,
+        poll_id: &PollUuid,
+        token: &EditToken,
+        vote: &VoteSubmission,
+    ) -> Result<(), ApiError> {
+        self.request(
+            "PUT",
+            &format!("/polls/{}/vote/{}", segment(poll_id), segment(token)),
+            Some(vote),
+        )
+        .await
+    }
+
+    /// Removes the ballot cast with `token`.
+    pub async fn withdraw_vote(
+        &self,
+        poll_id: &PollUuid,
+        token: &EditToken,
+    ) -> Result<(), ApiError> {
+        self.request(
+            "DELETE",
+            &format!("/polls/{}/vote/{}", segment(poll_id), segment(token)),
+            None::<&()>,
+        )
+        .await
+    }
+
+    /// A poll's results, for its creator to manage.
+    pub async fn get_admin(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+    ) -> Result<PollResults, ApiError> {
+        self.request(
+            "GET",
+            &format!("/polls/{}/admin/{}", segment(id), segment(token)),
+            None::<&()>,
+        )
+        .await
+    }
+
+    /// The invite links for a poll's roster.
+    pub async fn get_invites(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+    ) -> Result<Vec<Invite>, ApiError> {
+        self.request(
+            "GET",
+            &format!("/polls/{}/admin/{}/invites", segment(id), segment(token)),
+            None::<&()>,
+        )
+        .await
+    }
+
+    /// Who an invite link is for, and whether they've voted.
+    pub async fn get_invite(
+        &self,
+        id: &PollUuid,
+        token: &InviteToken,
+    ) -> Result<Participant, ApiError> {
+        self.request(
+            "GET",
+            &format!("/polls/{}/invites/{}", segment(id), segment(token)),
+            None::<&()>,
+        )
+        .await
+    }
+
+    /// Adds a choice to a poll that's collecting them, returning the poll as it stands.
+    pub async fn add_choice(&self, id: &PollUuid, choice: &NewChoice) -> Result<Poll, ApiError> {
+        self.request(
+            "POST",
+            &format!("/polls/{}/choices", segment(id)),
+            Some(choice),
+        )
+        .await
+    }
+
+    pub async fn advance_phase(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+        advance: &AdvancePhase,
+    ) -> Result<Poll, ApiError> {
+        self.request(
+            "POST",
+            &format!("/polls/{}/admin/{}/phase", segment(id), segment(token)),
+            Some(advance),
+        )
+        .await
+    }
+
+    pub async fn update_poll(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+        update: &UpdatePoll,
+    ) -> Result<Poll, ApiError> {
+        self.request(
+            "PATCH",
+            &format!("/polls/{}/admin/{}", segment(id), segment(token)),
+            Some(update),
+        )
+        .await
+    }
+
+    /// Opens a closed poll back up, with no closing time.
+    pub async fn reopen_poll(&self, id: &PollUuid, token: &AdminToken) -> Result<Poll, ApiError> {
+        self.request(
+            "POST",
+            &format!("/polls/{}/admin/{}/reopen", segment(id), segment(token)),
+            None::<&()>,
+        )
+        .await
+    }
+
+    /// Removes the ballot `vote` was cast with.
+    pub async fn delete_ballot(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+        vote: VoteId,
+    ) -> Result<(), ApiError> {
+        self.request(
+            "DELETE",
+            &format!(
+                "/polls/{}/admin/{}/votes/{}",
+                segment(id),
+                segment(token),
+                segment(vote)
+            ),
+            None::<&()>,
+        )
+        .await
+    }
+
+    pub async fn delete_poll(&self, id: &PollUuid, token: &AdminToken) -> Result<(), ApiError> {
+        self.request(
+            "DELETE",
+            &format!("/polls/{}/admin/{}", segment(id), segment(token)),
+            None::<&()>,
+        )
+        .await
+    }
+
+    /// Ends voting on a poll now, returning the poll as it stands.
+    pub async fn close_poll(&self, id: &PollUuid, token: &AdminToken) -> Result<Poll, ApiError> {
+        self.request(
+            "POST",
+            &format!("/polls/{}/admin/{}/close", segment(id), segment(token)),
+            None::<&()>,
+        )
+        .await
+    }
+
+    /// A poll's results, shown to the holder of `token` where they're hidden from others.
+    pub async fn get_results(
+        &self,
+        id: &PollUuid,
+        token: Option<&str>,
+    ) -> Result<PollResults, ApiError> {
+        let path = format!("/polls/{}/results{}", segment(id), token_query(token));
+        self.request("GET", &path, None::<&()>).await
+    }
+
+    /// Opens a stream of events for a poll, if the browser supports server-sent events.
+    pub fn watch(&self, id: &PollUuid, token: Option<&str>) -> Option<EventStream> {
+        EventStream::open(&format!(
+            "{}{}/polls/{}/events{}",
+            self.base_url,
+            PREFIX,
+            segment(id),
+            token_query(token)
+        ))
+    }
+
+    async fn request<B, T>(
+        &self,
+        method: &'static str,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T, ApiError>
+    where
+        B: Serialize,
+        T: DeserializeOwned,
+    {
+        let body = match body {
+            Some(body) => {
+                Some(serde_json::to_string(body).map_err(|e| ApiError::Network(e.to_string()))?)
+            }
+            None => None,
+        };
+        let response = self
+            .transport
+            .send(HttpRequest {
+                method,
+                url: format!("{}{}{}", self.base_url, PREFIX, path),
+                body,
+                timeout: self.timeout,
+            })
+            .await?;
+
+        if !(200..300).contains(&response.status) {
+            return Err(ApiError::from_response(
+                response.status,
+                response.retry_after,
+                &response.body,
+            ));
+        }
+        // Endpoints with nothing to say reply with an empty body
+        let body = if response.body.trim().is_empty() {
+            "null"
+        } else {
+            &response.body
+        };
+        serde_json::from_str(body).map_err(|e| ApiError::Network(e.to_string()))
+    }
+}
+
+/// Percent-encodes an id or token for use as a single path segment, so whatever's in it can't
+/// reach another endpoint.
+fn segment(value: impl Display) -> String {
+    js_sys::encode_uri_component(&value.to_string()).into()
+}
+
+/// The query string passing `token` to endpoints that show more to its holder.
+fn token_query(token: Option<&str>) -> String {
+    match token {
+        Some(token) => format!("?token={}", js_sys::encode_uri_component(token)),
+        None => String::new(),
+    }
+}
+
+/// A request started with `spawn`. Dropping it cancels the request, like yew's `FetchTask`.
+#[must_use]
+pub struct ApiTask(AbortHandle);
+
+impl Drop for ApiTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Runs `future` and sends the message it resolves to back to the component, unless the
+/// returned task is dropped first.
+pub fn spawn<C, F>(link: &ComponentLink<C>, future: F) -> ApiTask
+where
+    C: Component,
+    F: Future<Output = C::Message> + 'static,
+{
+    let (handle, registration) = AbortHandle::new_pair();
+    let link = link.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Ok(msg) = Abortable::new(future, registration).await {
+            link.send_message(msg);
+        }
+    });
+    ApiTask(handle)
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::api::ErrorBody;
+
+/// Everything that can go wrong talking to the backend.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// The request never got a response, or the response couldn't be understood.
+    Network(String),
+    /// The server took longer than the client's timeout to respond.
+    Timeout,
+    /// The poll doesn't exist.
+    NotFound,
+    /// The server refused because a token was missing or didn't match.
+    Forbidden { message: Option<String> },
+    /// The server rejected the request. `fields` maps field names to what was wrong with them.
+    Validation {
+        message: Option<String>,
+        fields: BTreeMap<String, Vec<String>>,
+    },
+    /// Too many requests, try again after `retry_after` seconds if the server said when.
+    RateLimited { retry_after: Option<u32> },
+    /// Any other unsuccessful response.
+    Server {
+        status: u16,
+        message: Option<String>,
+    },
+}
+
+impl ApiError {
+    /// Whether sending the same request again could succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::Network(_)
+            | ApiError::Timeout
+            | ApiError::RateLimited { .. }
+            | ApiError::Server { .. } => true,
+            ApiError::NotFound | ApiError::Forbidden { .. } | ApiError::Validation { .. } => false,
+        }
+    }
+
+    /// Decodes an unsuccessful response.
+    pub fn from_response(status: u16, retry_after: Option<u32>, body: &str) -> Self {
+        let body: ErrorBody = serde_json::from_str(body).unwrap_or_default();
+        match status {
+            403 => ApiError::Forbidden {
+                message: body.message,
+            },
+            404 => ApiError::NotFound,
+            400 | 422 => ApiError::Validation {
+                message: body.message,
+                fields: body.errors,
+            },
+            429 => ApiError::RateLimited { retry_after },
+            status => ApiError::Server {
+                status,
+                message: body.message,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Network(reason) => write!(f, "Couldn't reach the server: {}", reason),
+            ApiError::Timeout => write!(f, "The server took too long to respond."),
+            ApiError::NotFound => write!(f, "This poll doesn't exist."),
+            ApiError::Forbidden { message } => match message {
+                Some(message) => write!(f, "That isn't allowed: {}.", message),
+                None => write!(f, "That isn't allowed."),
+            },
+            ApiError::Validation { message, fields } => {
+                write!(
+                    f,
+                    "{}",
+                    message.as_deref().unwrap_or("The request was invalid.")
+                )?;
+                for (field, problems) in fields {
+                    write!(f, " {}: {}.", field, problems.join(", "))?;
+                }
+                Ok(())
+            }
+            ApiError::RateLimited {
+                retry_after: Some(seconds),
+            } => write!(f, "Too many requests, try again in {} seconds.", seconds),
+            ApiError::RateLimited { retry_after: None } => {
+                write!(f, "Too many requests, try again shortly.")
+            }
+            ApiError::Server { status, message } => match message {
+                Some(message) => write!(f, "The server had a problem ({}): {}", status, message),
+                None => write!(f, "The server had a problem ({}).", status),
+            },
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
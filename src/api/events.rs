@@ -0,0 +1,73 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc;
+use futures::Stream;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{EventSource, MessageEvent};
+
+use crate::api::PollEvent;
+
+/// Server-sent poll events, read with the browser's `EventSource`. The stream ends once the
+/// browser gives up reconnecting, and dropping it closes the connection.
+pub struct EventStream {
+    source: EventSource,
+    receiver: mpsc::UnboundedReceiver<PollEvent>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_error: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl EventStream {
+    pub fn open(url: &str) -> Option<Self> {
+        let source = EventSource::new(url).ok()?;
+        let (sender, receiver) = mpsc::unbounded();
+
+        let on_message = {
+            let sender = sender.clone();
+            Closure::wrap(Box::new(move |message: MessageEvent| {
+                let event = message
+                    .data()
+                    .as_string()
+                    .and_then(|data| serde_json::from_str(&data).ok());
+                if let Some(event) = event {
+                    let _ = sender.unbounded_send(event);
+                }
+            }) as Box<dyn FnMut(MessageEvent)>)
+        };
+        let on_error = {
+            let source = source.clone();
+            Closure::wrap(Box::new(move |_: web_sys::Event| {
+                // Transient errors are retried by the browser, only a closed source is final
+                if source.ready_state() == EventSource::CLOSED {
+                    sender.close_channel();
+                }
+            }) as Box<dyn FnMut(web_sys::Event)>)
+        };
+        source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+        Some(EventStream {
+            source,
+            receiver,
+            _on_message: on_message,
+            _on_error: on_error,
+        })
+    }
+}
+
+impl Stream for EventStream {
+    type Item = PollEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<PollEvent>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.source.set_onmessage(None);
+        self.source.set_onerror(None);
+        self.source.close();
+    }
+}
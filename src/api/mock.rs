@@ -0,0 +1,940 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use futures::future::{self, LocalBoxFuture};
+use serde::{Deserialize, Serialize};
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
+
+use crate::api::{
+    AdminToken, AdvancePhase, ApiError, BallotId, ChoiceId, CreatePoll, CreatePollResponse,
+    EditToken, Invite, InviteToken, NewChoice, Participant, Poll, PollBackend, PollChoice, PollId,
+    PollMetadata, PollPhase, PollResults, PollStatus, PollUuid, ResultsViewer, UpdatePoll, Vote,
+    VoteId, VoteReceipt, VoteSubmission,
+};
+use crate::time::now;
+
+const STORAGE_KEY: &str = "com.dotdotyew.mock";
+
+thread_local! {
+    static LOCAL: Rc<RefCell<Store>> = Rc::new(RefCell::new(Store::restore()));
+}
+
+/// Everything the mock backend knows about, laid out like the real database.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Store {
+    next_id: i32,
+    polls: Vec<PollMetadata>,
+    choices: Vec<PollChoice>,
+    votes: Vec<Vote>,
+    /// The vote rows each edit token's ballot became.
+    #[serde(default)]
+    ballots: BTreeMap<EditToken, Vec<VoteId>>,
+    #[serde(default)]
+    admin_tokens: BTreeMap<PollId, AdminToken>,
+    #[serde(default)]
+    invites: Vec<StoredInvite>,
+}
+
+/// An invite on a poll's roster, with the edit token of the ballot it last cast.
+#[derive(Serialize, Deserialize, Debug)]
+struct StoredInvite {
+    poll_id: PollId,
+    invite: Invite,
+    edit_token: Option<EditToken>,
+}
+
+impl Store {
+    fn restore() -> Self {
+        match StorageService::new(Area::Local) {
+            Ok(storage) => {
+                if let Json(Ok(mut store)) = storage.restore(STORAGE_KEY) {
+                    Store::backfill_ballots(&mut store);
+                    store
+                } else {
+                    Store::default()
+                }
+            }
+            Err(_) => Store::default(),
+        }
+    }
+
+    fn save(&self) {
+        if let Ok(mut storage) = StorageService::new(Area::Local) {
+            storage.store(STORAGE_KEY, Json(self));
+        }
+    }
+
+    /// Gives votes saved before ballot ids the id of the first row in their ballot.
+    fn backfill_ballots(&mut self) {
+        for i in 0..self.votes.len() {
+            if self.votes[i].ballot != BallotId::default() {
+                continue;
+            }
+            let id = self.votes[i].id;
+            let first = match self.ballots.values().find(|ids| ids.contains(&id)) {
+                Some(ids) => ids.iter().min().copied().unwrap_or(id),
+                // Cast before edit tokens, so the same voter at the same time
+                None => self
+                    .votes
+                    .iter()
+                    .filter(|v| {
+                        v.poll_id == self.votes[i].poll_id
+                            && v.voter == self.votes[i].voter
+                            && v.created_at == self.votes[i].created_at
+                    })
+                    .map(|v| v.id)
+                    .min()
+                    .unwrap_or(id),
+            };
+            self.votes[i].ballot = BallotId(first.0);
+        }
+    }
+
+    fn next_id(&mut self) -> i32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Adds a vote row for every choice the ballot gives something to, returning their ids.
+    fn insert_ballot(
+        &mut self,
+        poll: &Poll,
+        vote: &VoteSubmission,
+        token: &EditToken,
+    ) -> Vec<VoteId> {
+        let mut ids = Vec::new();
+        // Anonymous ballots keep neither the voter's name nor when they voted
+        let (voter, created_at) = if poll.poll.anonymous {
+            (format!("Anonymous {:08x}", random()), poll.poll.created_at)
+        } else if poll.poll.invite_only {
+            let invitee = self
+                .invites
+                .iter()
+                .find(|stored| stored.edit_token.as_ref() == Some(token))
+                .map(|stored| stored.invite.name.clone())
+                .unwrap_or_default();
+            (invitee, now())
+        } else {
+            (vote.voter.trim().to_owned(), now())
+        };
+        for (choice_id, dots) in vote.choices.iter().filter(|(_, dots)| **dots != 0) {
+            // Random ids on anonymous polls, like the server's, so neither they nor the order
+            // results come in give away when ballots were cast
+            let id = if poll.poll.anonymous {
+                let mut id = VoteId(-1 - (random() >> 1) as i32);
+                while self.votes.iter().any(|vote| vote.id == id) {
+                    id = VoteId(-1 - (random() >> 1) as i32);
+                }
+                id
+            } else {
+                VoteId(self.next_id())
+            };
+            self.votes.push(Vote {
+                id,
+                poll_id: poll.poll.id,
+                choice_id: *choice_id,
+                dots: *dots,
+                voter: voter.clone(),
+                ballot: BallotId::default(),
+                created_at,
+            });
+            ids.push(id);
+        }
+        if let Some(ballot) = ids.iter().min() {
+            for vote in self.votes.iter_mut().filter(|vote| ids.contains(&vote.id)) {
+                vote.ballot = BallotId(ballot.0);
+            }
+        }
+        ids
+    }
+
+    /// The poll, if `token` is its admin token.
+    fn admin_poll(&self, uuid: &PollUuid, token: &AdminToken) -> Result<Poll, ApiError> {
+        let poll = self.poll(uuid)?;
+        if self.admin_tokens.get(&poll.poll.id) != Some(token) {
+            return Err(forbidden("that admin token doesn't match this poll"));
+        }
+        Ok(poll)
+    }
+
+    /// Who holding `token` makes someone on `poll`, see the server's `find_viewer`.
+    fn viewer(&self, poll: &Poll, token: Option<&str>) -> ResultsViewer {
+        let token = match token {
+            Some(token) => token,
+            None => return ResultsViewer::Anyone,
+        };
+        if self
+            .admin_tokens
+            .get(&poll.poll.id)
+            .is_some_and(|admin| admin.as_str() == token)
+        {
+            return ResultsViewer::Creator;
+        }
+        if self.has_ballot(poll, &EditToken(token.to_owned())) {
+            ResultsViewer::Voter
+        } else {
+            ResultsViewer::Anyone
+        }
+    }
+
+    /// Whether the ballot cast with `token` still has votes on `poll`.
+    fn has_ballot(&self, poll: &Poll, token: &EditToken) -> bool {
+        self.ballots.get(token).is_some_and(|ids| {
+            self.votes
+                .iter()
+                .any(|vote| vote.poll_id == poll.poll.id && ids.contains(&vote.id))
+        })
+    }
+
+    /// Who an invite on `poll` is for and whether their ballot is still there.
+    fn invite(&self, poll: &Poll, token: &InviteToken) -> Result<Participant, ApiError> {
+        let stored = self
+            .invites
+            .iter()
+            .find(|stored| stored.poll_id == poll.poll.id && &stored.invite.token == token)
+            .ok_or_else(|| forbidden("that invite doesn't match this poll"))?;
+        Ok(self.participant(poll, stored))
+    }
+
+    fn participant(&self, poll: &Poll, stored: &StoredInvite) -> Participant {
+        Participant {
+            name: stored.invite.name.clone(),
+            voted: stored
+                .edit_token
+                .as_ref()
+                .is_some_and(|token| self.has_ballot(poll, token)),
+        }
+    }
+
+    fn poll(&self, uuid: &PollUuid) -> Result<Poll, ApiError> {
+        let poll = self
+            .polls
+            .iter()
+            .find(|poll| &poll.uuid == uuid)
+            .ok_or(ApiError::NotFound)?;
+        Ok(Poll {
+            poll: poll.clone(),
+            choices: self
+                .choices
+                .iter()
+                .filter(|choice| choice.poll_id == poll.id)
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+fn invalid(field: &str, message: &str) -> ApiError {
+    let mut fields = BTreeMap::new();
+    fields.insert(field.to_owned(), vec![message.to_owned()]);
+    ApiError::Validation {
+        message: None,
+        fields,
+    }
+}
+
+/// A backend that keeps polls in memory instead of talking to a server, for offline
+/// development and tests. It applies the same validation the real backend does.
+#[derive(Clone)]
+pub struct MockBackend {
+    store: Rc<RefCell<Store>>,
+    persist: bool,
+}
+
+impl MockBackend {
+    /// An empty backend that forgets everything once dropped.
+    pub fn in_memory() -> Self {
+        MockBackend {
+            store: Rc::new(RefCell::new(Store::default())),
+            persist: false,
+        }
+    }
+
+    /// The backend shared by the whole app, saved to localStorage so polls survive reloads.
+    pub fn local() -> Self {
+        MockBackend {
+            store: LOCAL.with(|store| store.clone()),
+            persist: true,
+        }
+    }
+
+    fn save(&self) {
+        if self.persist {
+            self.store.borrow().save();
+        }
+    }
+
+    fn create(&self, create: &CreatePoll) -> Result<CreatePollResponse, ApiError> {
+        ensure_valid(create.validate(now()))?;
+
+        let mut store = self.store.borrow_mut();
+        let id = PollId(store.next_id());
+        let uuid = PollUuid(format!("00000000-0000-4000-8000-{:012x}", id.0));
+        store.polls.push(PollMetadata {
+            id,
+            uuid: uuid.clone(),
+            title: create.title.clone(),
+            created_at: now(),
+            voting_method: create.voting_method,
+            dot_budget: create.dot_budget,
+            rules: create.rules.clone(),
+            funding: create.funding,
+            opens_at: create.opens_at,
+            closes_at: create.closes_at,
+            phase: create.brainstorm.then_some(PollPhase::Collect),
+            categories: create.categories.clone(),
+            results_visibility: create.results_visibility,
+            anonymous: create.anonymous,
+            invite_only: create.roster_names().next().is_some(),
+        });
+        let admin_token = AdminToken(format!("mock-admin-{}", id));
+        store.admin_tokens.insert(id, admin_token.clone());
+        for (details, cost, category) in create.new_choices() {
+            let choice_id = ChoiceId(store.next_id());
+            store.choices.push(PollChoice {
+                id: choice_id,
+                poll_id: id,
+                details: details.to_owned(),
+                cost,
+                category,
+                created_at: now(),
+            });
+        }
+        let mut invites = Vec::new();
+        for name in create.roster_names() {
+            let invite = Invite {
+                name: name.to_owned(),
+                token: InviteToken(format!("mock-invite-{}", store.next_id())),
+            };
+            store.invites.push(StoredInvite {
+                poll_id: id,
+                invite: invite.clone(),
+                edit_token: None,
+            });
+            invites.push(invite);
+        }
+        drop(store);
+        self.save();
+        Ok(CreatePollResponse {
+            poll: uuid,
+            admin_token: Some(admin_token),
+            invites,
+        })
+    }
+
+    fn cast(&self, poll_id: &PollUuid, vote: &VoteSubmission) -> Result<VoteReceipt, ApiError> {
+        let mut store = self.store.borrow_mut();
+        let poll = store.poll(poll_id)?;
+        ensure_open(&poll)?;
+        ensure_valid(poll.validate_vote(vote))?;
+        let edit_token = EditToken(format!("mock-{}-{}", poll.poll.id, store.next_id()));
+        if poll.poll.invite_only {
+            let invite = vote.invite.as_ref().ok_or_else(|| {
+                forbidden("this poll is invite only, vote through your invite link")
+            })?;
+            if store.invite(&poll, invite)?.voted {
+                return Err(forbidden(
+                    "that invite has already voted, change the vote instead",
+                ));
+            }
+            if let Some(stored) = store
+                .invites
+                .iter_mut()
+                .find(|stored| stored.poll_id == poll.poll.id && &stored.invite.token == invite)
+            {
+                stored.edit_token = Some(edit_token.clone());
+            }
+        }
+        let ids = store.insert_ballot(&poll, vote, &edit_token);
+        store.ballots.insert(edit_token.clone(), ids);
+        drop(store);
+        self.save();
+        Ok(VoteReceipt { edit_token })
+    }
+
+    fn change(
+        &self,
+        poll_id: &PollUuid,
+        token: &EditToken,
+        vote: Option<&VoteSubmission>,
+    ) -> Result<(), ApiError> {
+        let mut store = self.store.borrow_mut();
+        let poll = store.poll(poll_id)?;
+        ensure_open(&poll)?;
+        if let Some(vote) = vote {
+            ensure_valid(poll.validate_vote(vote))?;
+        }
+        let ids = store
+            .ballots
+            .remove(token)
+            .filter(|ids| {
+                store
+                    .votes
+                    .iter()
+                    .any(|v| ids.contains(&v.id) && v.poll_id == poll.poll.id)
+            })
+            .ok_or_else(|| forbidden("no ballot on this poll matches that edit token"))?;
+        store.votes.retain(|v| !ids.contains(&v.id));
+        if let Some(vote) = vote {
+            let ids = store.insert_ballot(&poll, vote, token);
+            store.ballots.insert(token.clone(), ids);
+        }
+        drop(store);
+        self.save();
+        Ok(())
+    }
+
+    fn admin_results(&self, id: &PollUuid, token: &AdminToken) -> Result<PollResults, ApiError> {
+        self.store.borrow().admin_poll(id, token)?;
+        self.results(id)
+    }
+
+    fn update(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+        update: &UpdatePoll,
+    ) -> Result<Poll, ApiError> {
+        let mut store = self.store.borrow_mut();
+        let poll = store.admin_poll(id, token)?;
+        ensure_valid(poll.validate_update(update))?;
+        let kept: Vec<ChoiceId> = update.listed_choices().filter_map(|c| c.id).collect();
+        store
+            .choices
+            .retain(|choice| choice.poll_id != poll.poll.id || kept.contains(&choice.id));
+        store
+            .votes
+            .retain(|vote| vote.poll_id != poll.poll.id || kept.contains(&vote.choice_id));
+        if let Some(stored) = store.polls.iter_mut().find(|p| p.id == poll.poll.id) {
+            stored.title = update.title.trim().to_owned();
+        }
+        for listed in update.listed_choices() {
+            match listed.id {
+                Some(id) => {
+                    if let Some(choice) = store.choices.iter_mut().find(|c| c.id == id) {
+                        choice.details = listed.details.trim().to_owned();
+                        choice.cost = listed.cost;
+                        choice.category = listed.category;
+                    }
+                }
+                None => {
+                    let choice_id = ChoiceId(store.next_id());
+                    store.choices.push(PollChoice {
+                        id: choice_id,
+                        poll_id: poll.poll.id,
+                        details: listed.details.trim().to_owned(),
+                        cost: listed.cost,
+                        category: listed.category,
+                        created_at: now(),
+                    });
+                }
+            }
+        }
+        let poll = store.poll(id)?;
+        drop(store);
+        self.save();
+        Ok(poll)
+    }
+
+    /// Closes the poll now, or reopens it with no closing time.
+    fn set_closed(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+        closed: bool,
+    ) -> Result<Poll, ApiError> {
+        let mut store = self.store.borrow_mut();
+        let mut poll = store.admin_poll(id, token)?;
+        if !closed && poll.poll.phase == Some(PollPhase::Reveal) {
+            return Err(invalid(
+                "phase",
+                "polls can't be reopened once their results are revealed",
+            ));
+        }
+        let is_closed = poll.poll.status(now()) == PollStatus::Closed;
+        if closed != is_closed {
+            poll.poll.closes_at = if closed { Some(now()) } else { None };
+            if let Some(stored) = store.polls.iter_mut().find(|p| p.id == poll.poll.id) {
+                stored.closes_at = poll.poll.closes_at;
+            }
+        }
+        drop(store);
+        self.save();
+        Ok(poll)
+    }
+
+    fn delete_ballot_of(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+        vote_id: VoteId,
+    ) -> Result<(), ApiError> {
+        let mut store = self.store.borrow_mut();
+        let poll = store.admin_poll(id, token)?;
+        let vote = store
+            .votes
+            .iter()
+            .find(|vote| vote.id == vote_id && vote.poll_id == poll.poll.id)
+            .cloned()
+            .ok_or_else(|| invalid("vote", "isn't a vote on this poll"))?;
+        let ballot = store
+            .ballots
+            .iter()
+            .find(|(_, ids)| ids.contains(&vote_id))
+            .map(|(token, _)| token.clone());
+        let ids: Vec<VoteId> = match ballot {
+            Some(token) => store.ballots.remove(&token).unwrap_or_default(),
+            // Cast before edit tokens, so the same voter at the same time
+            None => store
+                .votes
+                .iter()
+                .filter(|v| {
+                    v.poll_id == vote.poll_id
+                        && v.voter == vote.voter
+                        && v.created_at == vote.created_at
+                })
+                .map(|v| v.id)
+                .collect(),
+        };
+        store.votes.retain(|v| !ids.contains(&v.id));
+        drop(store);
+        self.save();
+        Ok(())
+    }
+
+    fn delete(&self, id: &PollUuid, token: &AdminToken) -> Result<(), ApiError> {
+        let mut store = self.store.borrow_mut();
+        let poll_id = store.admin_poll(id, token)?.poll.id;
+        store.polls.retain(|poll| poll.id != poll_id);
+        store.choices.retain(|choice| choice.poll_id != poll_id);
+        store.votes.retain(|vote| vote.poll_id != poll_id);
+        store.admin_tokens.remove(&poll_id);
+        store.invites.retain(|stored| stored.poll_id != poll_id);
+        drop(store);
+        self.save();
+        Ok(())
+    }
+
+    fn invites(&self, id: &PollUuid, token: &AdminToken) -> Result<Vec<Invite>, ApiError> {
+        let store = self.store.borrow();
+        let poll = store.admin_poll(id, token)?;
+        Ok(store
+            .invites
+            .iter()
+            .filter(|stored| stored.poll_id == poll.poll.id)
+            .map(|stored| stored.invite.clone())
+            .collect())
+    }
+
+    fn add(&self, id: &PollUuid, new: &NewChoice) -> Result<Poll, ApiError> {
+        let mut store = self.store.borrow_mut();
+        let poll = store.poll(id)?;
+        if !poll.poll.collecting() {
+            return Err(forbidden("this poll isn't collecting choices"));
+        }
+        ensure_valid(poll.validate_new_choice(new))?;
+        let choice_id = ChoiceId(store.next_id());
+        store.choices.push(PollChoice {
+            id: choice_id,
+            poll_id: poll.poll.id,
+            details: new.details.trim().to_owned(),
+            cost: None,
+            category: new.category,
+            created_at: now(),
+        });
+        let poll = store.poll(id)?;
+        drop(store);
+        self.save();
+        Ok(poll)
+    }
+
+    fn advance(
+        &self,
+        id: &PollUuid,
+        token: &AdminToken,
+        advance: &AdvancePhase,
+    ) -> Result<Poll, ApiError> {
+        let mut store = self.store.borrow_mut();
+        let poll = store.admin_poll(id, token)?;
+        ensure_valid(poll.validate_advance(advance))?;
+        let merged: Vec<ChoiceId> = advance
+            .merges
+            .iter()
+            .flat_map(|merge| merge.from.iter().copied())
+            .collect();
+        store.choices.retain(|choice| !merged.contains(&choice.id));
+        if let Some(stored) = store.polls.iter_mut().find(|p| p.id == poll.poll.id) {
+            stored.phase = Some(advance.phase);
+        }
+        let poll = store.poll(id)?;
+        drop(store);
+        self.save();
+        Ok(poll)
+    }
+
+    /// The results as anyone can see them, hidden until a brainstorming poll reveals them.
+    fn public_results(&self, id: &PollUuid, token: Option<&str>) -> Result<PollResults, ApiError> {
+        let viewer = {
+            let store = self.store.borrow();
+            store.viewer(&store.poll(id)?, token)
+        };
+        let results = self.results(id)?;
+        if !results.poll.results_visible_to(viewer, now()) {
+            return Err(forbidden(results.poll.results_hidden_reason()));
+        }
+        Ok(results)
+    }
+
+    fn results(&self, id: &PollUuid) -> Result<PollResults, ApiError> {
+        let store = self.store.borrow();
+        let poll = store.poll(id)?;
+        let participation = store
+            .invites
+            .iter()
+            .filter(|stored| stored.poll_id == poll.poll.id)
+            .map(|stored| store.participant(&poll, stored))
+            .collect();
+        let mut votes: Vec<Vote> = store
+            .votes
+            .iter()
+            .filter(|vote| vote.poll_id == poll.poll.id)
+            .cloned()
+            .collect();
+        votes.sort_by_key(|vote| vote.id);
+        Ok(PollResults {
+            participation,
+            votes,
+            poll: poll.poll,
+            choices: poll.choices,
+        })
+    }
+}
+
+/// A random number, for anonymous ballots' ids and labels.
+fn random() -> u32 {
+    #[cfg(target_arch = "wasm32")]
+    let random = (js_sys::Math::random() * f64::from(u32::MAX)) as u32;
+    // Outside the browser there's no `Math`, which only the tests run into
+    #[cfg(not(target_arch = "wasm32"))]
+    let random = {
+        use std::hash::BuildHasher;
+        std::collections::hash_map::RandomState::new().hash_one(()) as u32
+    };
+    random
+}
+
+fn forbidden(message: &str) -> ApiError {
+    ApiError::Forbidden {
+        message: Some(message.to_owned()),
+    }
+}
+
+fn ensure_open(poll: &Poll) -> Result<(), ApiError> {
+    match poll.poll.status(now()) {
+        PollStatus::Scheduled => Err(forbidden("voting hasn't opened yet")),
+        PollStatus::Closed => Err(forbidden("voting has closed")),
+        PollStatus::Open if poll.poll.collecting() => {
+            Err(forbidden("choices are still being collected"))
+        }
+        PollStatus::Open => Ok(()),
+    }
+}
+
+/// Turns the problems a check found into an error, if there were any.
+fn ensure_valid(fields: BTreeMap<String, Vec<String>>) -> Result<(), ApiError> {
+    if fields.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::Validation {
+            message: None,
+            fields,
+        })
+    }
+}
+
+impl PollBackend for MockBackend {
+    fn get_poll<'a>(&'a self, id: &'a PollUuid) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(future::ready(self.store.borrow().poll(id)))
+    }
+
+    fn create_poll<'a>(
+        &'a self,
+        poll: &'a CreatePoll,
+    ) -> LocalBoxFuture<'a, Result<CreatePollResponse, ApiError>> {
+        Box::pin(future::ready(self.create(poll)))
+    }
+
+    fn vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        vote: &'a VoteSubmission,
+    ) -> LocalBoxFuture<'a, Result<VoteReceipt, ApiError>> {
+        Box::pin(future::ready(self.cast(poll_id, vote)))
+    }
+
+    fn change_vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        token: &'a EditToken,
+        vote: &'a VoteSubmission,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(future::ready(self.change(poll_id, token, Some(vote))))
+    }
+
+    fn withdraw_vote<'a>(
+        &'a self,
+        poll_id: &'a PollUuid,
+        token: &'a EditToken,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(future::ready(self.change(poll_id, token, None)))
+    }
+
+    fn add_choice<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        choice: &'a NewChoice,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(future::ready(self.add(id, choice)))
+    }
+
+    fn get_invite<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a InviteToken,
+    ) -> LocalBoxFuture<'a, Result<Participant, ApiError>> {
+        let store = self.store.borrow();
+        Box::pin(future::ready(
+            store.poll(id).and_then(|poll| store.invite(&poll, token)),
+        ))
+    }
+
+    fn advance_phase<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        advance: &'a AdvancePhase,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(future::ready(self.advance(id, token, advance)))
+    }
+
+    fn get_admin<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<PollResults, ApiError>> {
+        Box::pin(future::ready(self.admin_results(id, token)))
+    }
+
+    fn update_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        update: &'a UpdatePoll,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(future::ready(self.update(id, token, update)))
+    }
+
+    fn get_invites<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Vec<Invite>, ApiError>> {
+        Box::pin(future::ready(self.invites(id, token)))
+    }
+
+    fn close_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(future::ready(self.set_closed(id, token, true)))
+    }
+
+    fn reopen_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<Poll, ApiError>> {
+        Box::pin(future::ready(self.set_closed(id, token, false)))
+    }
+
+    fn delete_ballot<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+        vote: VoteId,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(future::ready(self.delete_ballot_of(id, token, vote)))
+    }
+
+    fn delete_poll<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: &'a AdminToken,
+    ) -> LocalBoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(future::ready(self.delete(id, token)))
+    }
+
+    fn get_results<'a>(
+        &'a self,
+        id: &'a PollUuid,
+        token: Option<&'a str>,
+    ) -> LocalBoxFuture<'a, Result<PollResults, ApiError>> {
+        Box::pin(future::ready(self.public_results(id, token)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    use super::*;
+
+    /// A three choice dot voting poll with three dots each, plus whatever's in `extra`.
+    fn create(extra: serde_json::Value) -> CreatePoll {
+        let mut create = json!({
+            "title": "Lunch",
+            "choices": ["Pizza", "Tacos", "Ramen"],
+            "dot_budget": {"type": "fixed", "dots": 3},
+            "rules": {},
+        });
+        if let (Some(create), Some(extra)) = (create.as_object_mut(), extra.as_object()) {
+            create.extend(extra.clone());
+        }
+        serde_json::from_value(create).unwrap()
+    }
+
+    fn submission(poll: &Poll, voter: &str, dots: &[i32]) -> VoteSubmission {
+        VoteSubmission {
+            voter: voter.into(),
+            choices: poll
+                .choices
+                .iter()
+                .zip(dots)
+                .map(|(choice, dots)| (choice.id, *dots))
+                .collect(),
+            invite: None,
+        }
+    }
+
+    /// Each voter's dots, in the order the ballots were cast and then by choice, since a
+    /// ballot's dots come from a map with no order of its own.
+    fn dots(results: &PollResults) -> Vec<(String, i32)> {
+        let mut votes: Vec<_> = results.votes.iter().collect();
+        let cast = |vote: &Vote| results.votes.iter().position(|v| v.ballot == vote.ballot);
+        let choice = |vote: &Vote| results.choices.iter().position(|c| c.id == vote.choice_id);
+        votes.sort_by_key(|vote| (cast(vote), choice(vote)));
+        votes
+            .into_iter()
+            .map(|vote| (vote.voter.clone(), vote.dots))
+            .collect()
+    }
+
+    fn field(error: ApiError) -> Vec<String> {
+        match error {
+            ApiError::Validation { fields, .. } => fields.into_keys().collect(),
+            error => panic!("expected a validation error, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn votes_show_up_in_the_results() {
+        let backend = MockBackend::in_memory();
+        let created = block_on(backend.create_poll(&create(json!({})))).unwrap();
+        let poll = block_on(backend.get_poll(&created.poll)).unwrap();
+        assert_eq!(poll.poll.title, "Lunch");
+        assert_eq!(poll.choices.len(), 3);
+
+        block_on(backend.vote(&created.poll, &submission(&poll, "Ana", &[2, 1, 0]))).unwrap();
+        block_on(backend.vote(&created.poll, &submission(&poll, "Bo", &[0, 0, 3]))).unwrap();
+        let results = block_on(backend.get_results(&created.poll, None)).unwrap();
+        assert_eq!(
+            dots(&results),
+            [("Ana".into(), 2), ("Ana".into(), 1), ("Bo".into(), 3)]
+        );
+        assert_eq!(results.votes[0].ballot, results.votes[1].ballot);
+        assert_ne!(results.votes[0].ballot, results.votes[2].ballot);
+    }
+
+    #[test]
+    fn ballots_can_be_changed_and_withdrawn() {
+        let backend = MockBackend::in_memory();
+        let created = block_on(backend.create_poll(&create(json!({})))).unwrap();
+        let poll = block_on(backend.get_poll(&created.poll)).unwrap();
+        let receipt =
+            block_on(backend.vote(&created.poll, &submission(&poll, "Ana", &[3, 0, 0]))).unwrap();
+        block_on(backend.vote(&created.poll, &submission(&poll, "Bo", &[0, 3, 0]))).unwrap();
+
+        let change = submission(&poll, "Ana", &[0, 1, 2]);
+        block_on(backend.change_vote(&created.poll, &receipt.edit_token, &change)).unwrap();
+        let results = block_on(backend.get_results(&created.poll, None)).unwrap();
+        assert_eq!(
+            dots(&results),
+            [("Bo".into(), 3), ("Ana".into(), 1), ("Ana".into(), 2)]
+        );
+
+        block_on(backend.withdraw_vote(&created.poll, &receipt.edit_token)).unwrap();
+        let results = block_on(backend.get_results(&created.poll, None)).unwrap();
+        assert_eq!(dots(&results), [("Bo".into(), 3)]);
+        assert!(matches!(
+            block_on(backend.withdraw_vote(&created.poll, &receipt.edit_token)),
+            Err(ApiError::Forbidden { .. })
+        ));
+    }
+
+    #[test]
+    fn requests_are_validated_like_the_server() {
+        let backend = MockBackend::in_memory();
+        let error = block_on(backend.create_poll(&create(json!({"title": " "})))).unwrap_err();
+        assert_eq!(field(error), ["title"]);
+        let anonymous_roster = create(json!({"anonymous": true, "roster": ["Ana"]}));
+        let error = block_on(backend.create_poll(&anonymous_roster)).unwrap_err();
+        assert_eq!(field(error), ["roster"]);
+
+        let created = block_on(backend.create_poll(&create(json!({})))).unwrap();
+        let poll = block_on(backend.get_poll(&created.poll)).unwrap();
+        let error = block_on(backend.vote(&created.poll, &submission(&poll, "Ana", &[1, 0, 0])))
+            .unwrap_err();
+        assert_eq!(field(error), ["choices"]);
+    }
+
+    #[test]
+    fn closed_polls_take_no_votes() {
+        let backend = MockBackend::in_memory();
+        let created = block_on(backend.create_poll(&create(json!({})))).unwrap();
+        let token = created.admin_token.unwrap();
+        let poll = block_on(backend.close_poll(&created.poll, &token)).unwrap();
+        assert!(matches!(
+            block_on(backend.vote(&created.poll, &submission(&poll, "Ana", &[3, 0, 0]))),
+            Err(ApiError::Forbidden { .. })
+        ));
+        let poll = block_on(backend.reopen_poll(&created.poll, &token)).unwrap();
+        block_on(backend.vote(&created.poll, &submission(&poll, "Ana", &[3, 0, 0]))).unwrap();
+    }
+
+    #[test]
+    fn anonymous_ballots_dont_give_away_their_order() {
+        let backend = MockBackend::in_memory();
+        let created = block_on(backend.create_poll(&create(json!({"anonymous": true})))).unwrap();
+        let poll = block_on(backend.get_poll(&created.poll)).unwrap();
+        for voter in ["Ana", "Bo", "Cy"] {
+            block_on(backend.vote(&created.poll, &submission(&poll, voter, &[2, 1, 0]))).unwrap();
+        }
+        let results = block_on(backend.get_results(&created.poll, None)).unwrap();
+        assert_eq!(results.votes.len(), 6);
+        assert!(results.votes.iter().all(|vote| vote.id.0 < 0));
+        assert!(results.votes.windows(2).all(|pair| pair[0].id < pair[1].id));
+        assert!(results
+            .votes
+            .iter()
+            .all(|vote| vote.voter.starts_with("Anonymous ") && vote.voter != "Anonymous 1"));
+        for vote in &results.votes {
+            let ballot = results.votes.iter().filter(|v| v.ballot == vote.ballot);
+            assert_eq!(ballot.clone().count(), 2);
+            assert_eq!(ballot.map(|v| v.id.0).min(), Some(vote.ballot.0));
+        }
+    }
+}
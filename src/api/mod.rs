@@ -0,0 +1,21 @@
+mod backend;
+mod client;
+mod error;
+mod events;
+#[cfg(feature = "mock-backend")]
+mod mock;
+mod transport;
+
+pub use backend::{backend, PollBackend};
+pub use client::{spawn, ApiClient, ApiTask};
+pub use error::ApiError;
+pub use events::EventStream;
+#[cfg(feature = "mock-backend")]
+pub use mock::MockBackend;
+pub use transport::{FetchTransport, HttpRequest, HttpResponse, Transport};
+
+pub use dotdotvote_types::v1::*;
+pub use dotdotvote_types::{
+    tally, AdminToken, BallotId, ChoiceId, EditToken, InviteToken, PollId, PollUuid, Timestamp,
+    VoteId,
+};
@@ -0,0 +1,150 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use futures::future::LocalBoxFuture;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortController, Request, RequestInit, Response};
+
+use crate::api::ApiError;
+
+/// A request for a `Transport` to send.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: &'static str,
+    pub url: String,
+    /// A JSON body, if there is one.
+    pub body: Option<String>,
+    /// How long to wait for a response before giving up with `ApiError::Timeout`.
+    pub timeout: Option<Duration>,
+}
+
+/// Whatever came back for an `HttpRequest`, successful or not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// The value of the `Retry-After` header, in seconds.
+    pub retry_after: Option<u32>,
+    pub body: String,
+}
+
+/// Gets requests to the backend and back. Dropping the returned future must cancel the request.
+pub trait Transport {
+    fn send(&self, request: HttpRequest)
+        -> LocalBoxFuture<'static, Result<HttpResponse, ApiError>>;
+}
+
+/// Sends requests with the browser's `fetch`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FetchTransport;
+
+impl Transport for FetchTransport {
+    fn send(
+        &self,
+        request: HttpRequest,
+    ) -> LocalBoxFuture<'static, Result<HttpResponse, ApiError>> {
+        Box::pin(fetch(request))
+    }
+}
+
+/// Aborts an in-flight fetch when dropped, which is how a cancelled request stops the browser
+/// from finishing it. Also clears the timeout timer, if one was set.
+struct AbortGuard {
+    controller: AbortController,
+    timer: Option<(i32, Closure<dyn FnMut()>)>,
+    finished: bool,
+}
+
+impl Drop for AbortGuard {
+    fn drop(&mut self) {
+        if let Some((handle, _)) = self.timer.take() {
+            if let Some(window) = web_sys::window() {
+                window.clear_timeout_with_handle(handle);
+            }
+        }
+        if !self.finished {
+            self.controller.abort();
+        }
+    }
+}
+
+fn js_error(error: JsValue) -> ApiError {
+    ApiError::Network(
+        error
+            .as_string()
+            .or_else(|| {
+                error
+                    .dyn_ref::<js_sys::Error>()
+                    .map(|e| String::from(e.message()))
+            })
+            .unwrap_or_else(|| "the request failed".into()),
+    )
+}
+
+async fn fetch(request: HttpRequest) -> Result<HttpResponse, ApiError> {
+    let window = web_sys::window().ok_or_else(|| ApiError::Network("no window".into()))?;
+    let controller = AbortController::new().map_err(js_error)?;
+
+    let init = RequestInit::new();
+    init.set_method(request.method);
+    init.set_signal(Some(&controller.signal()));
+    if let Some(body) = &request.body {
+        init.set_body(&JsValue::from_str(body));
+    }
+    let web_request = Request::new_with_str_and_init(&request.url, &init).map_err(js_error)?;
+    if request.body.is_some() {
+        web_request
+            .headers()
+            .set("Content-Type", "application/json")
+            .map_err(js_error)?;
+    }
+
+    let timed_out = Rc::new(Cell::new(false));
+    let mut guard = AbortGuard {
+        controller,
+        timer: None,
+        finished: false,
+    };
+    if let Some(timeout) = request.timeout {
+        let controller = guard.controller.clone();
+        let timed_out = timed_out.clone();
+        let on_timeout = Closure::wrap(Box::new(move || {
+            timed_out.set(true);
+            controller.abort();
+        }) as Box<dyn FnMut()>);
+        let handle = window
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                on_timeout.as_ref().unchecked_ref(),
+                timeout.as_millis() as i32,
+            )
+            .map_err(js_error)?;
+        guard.timer = Some((handle, on_timeout));
+    }
+
+    let result = async {
+        let response: Response = JsFuture::from(window.fetch_with_request(&web_request))
+            .await?
+            .dyn_into()?;
+        let body = JsFuture::from(response.text()?).await?;
+        Ok((response, body.as_string().unwrap_or_default()))
+    }
+    .await;
+    guard.finished = true;
+
+    match result {
+        Ok((response, body)) => Ok(HttpResponse {
+            status: response.status(),
+            retry_after: response
+                .headers()
+                .get("Retry-After")
+                .ok()
+                .flatten()
+                .and_then(|value| value.trim().parse().ok()),
+            body,
+        }),
+        Err(_) if timed_out.get() => Err(ApiError::Timeout),
+        Err(error) => Err(js_error(error)),
+    }
+}
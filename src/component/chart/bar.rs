@@ -0,0 +1,95 @@
+use super::{percent, short_label, ChartRow};
+use crate::component::{Pure, PureComponent};
+use yew::prelude::*;
+
+pub type BarChart = Pure<BarChartImpl>;
+
+const WIDTH: f64 = 600.0;
+const LABEL_WIDTH: f64 = 180.0;
+const VALUE_WIDTH: f64 = 50.0;
+const ROW_HEIGHT: f64 = 32.0;
+const BAR_HEIGHT: f64 = 20.0;
+
+/// Horizontal bars, one per row, scaled against the longest bar. Percentages are of every
+/// row's positive segments added up, like the share of dots the results list shows.
+#[derive(Clone, Properties, PartialEq)]
+pub struct BarChartImpl {
+    pub rows: Vec<ChartRow>,
+    /// Splits each bar into its segments instead of filling it with the row's colour.
+    #[prop_or_default]
+    pub stacked: bool,
+}
+
+impl BarChartImpl {
+    fn view_row(&self, i: usize, row: &ChartRow, scale: f64, total: i32) -> Html {
+        let y = i as f64 * ROW_HEIGHT;
+        let bar_y = y + (ROW_HEIGHT - BAR_HEIGHT) / 2.0;
+        let text_y = y + ROW_HEIGHT / 2.0;
+        let row_total = row.total();
+        // Dots against a choice can leave it below zero, which gets no bar. Stacked bars only
+        // draw the segments above zero, so they run past the net total.
+        let bar_width = if self.stacked {
+            f64::from(row.positive()) * scale
+        } else {
+            f64::from(row_total.max(0)) * scale
+        };
+        let bars = if self.stacked {
+            let mut x = LABEL_WIDTH;
+            row.segments
+                .iter()
+                .filter(|segment| segment.value > 0)
+                .map(|segment| {
+                    let width = f64::from(segment.value) * scale;
+                    let rect = html! {
+                        <rect x=x y=bar_y width=width height=BAR_HEIGHT fill=segment.colour
+                            stroke="white">
+                            <title>{format!("{}: {}", segment.label, segment.value)}</title>
+                        </rect>
+                    };
+                    x += width;
+                    rect
+                })
+                .collect::<Html>()
+        } else {
+            html! {
+                <rect x=LABEL_WIDTH y=bar_y width=bar_width height=BAR_HEIGHT
+                    fill=row.colour>
+                    <title>{format!("{}: {}", row.label, row_total)}</title>
+                </rect>
+            }
+        };
+        html! {
+            <g>
+                <text x=LABEL_WIDTH - 8.0 y=text_y text-anchor="end" dominant-baseline="middle">
+                    <title>{&row.label}</title>
+                    {short_label(&row.label)}
+                </text>
+                { bars }
+                <text x=LABEL_WIDTH + bar_width + 6.0 y=text_y
+                    dominant-baseline="middle" font-size="small">
+                    <title>{format!("{:.1}%", percent(row_total, total))}</title>
+                    {row_total}
+                </text>
+            </g>
+        }
+    }
+}
+
+impl PureComponent for BarChartImpl {
+    fn view(&self) -> Html {
+        let length = if self.stacked {
+            ChartRow::positive
+        } else {
+            ChartRow::total
+        };
+        let max = self.rows.iter().map(length).max().unwrap_or(0).max(1);
+        let total = self.rows.iter().map(ChartRow::positive).sum();
+        let scale = (WIDTH - LABEL_WIDTH - VALUE_WIDTH) / f64::from(max);
+        let height = (self.rows.len() as f64 * ROW_HEIGHT).max(ROW_HEIGHT);
+        html! {
+            <svg viewBox=format!("0 0 {} {}", WIDTH, height) width="100%" role="img">
+                { for self.rows.iter().enumerate().map(|(i, row)| self.view_row(i, row, scale, total)) }
+            </svg>
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Charts drawn as SVG straight from Rust, so the results page doesn't need a JS library.
+
+mod bar;
+mod pie;
+
+pub use bar::BarChart;
+pub use pie::PieChart;
+
+/// Longest label drawn inside a chart before it's cut short, full labels show on hover.
+const MAX_LABEL: usize = 24;
+
+/// One bar or slice of a chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartRow {
+    pub label: String,
+    pub colour: &'static str,
+    /// What the row is made up of, drawn in order when bars are stacked.
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub label: String,
+    pub colour: &'static str,
+    pub value: i32,
+}
+
+impl ChartRow {
+    pub fn total(&self) -> i32 {
+        self.segments.iter().map(|segment| segment.value).sum()
+    }
+
+    /// The segments above zero added up, which is all a stacked bar draws.
+    pub fn positive(&self) -> i32 {
+        self.segments
+            .iter()
+            .map(|segment| segment.value.max(0))
+            .sum()
+    }
+}
+
+fn short_label(label: &str) -> String {
+    if label.chars().count() > MAX_LABEL {
+        label.chars().take(MAX_LABEL - 1).chain(Some('…')).collect()
+    } else {
+        label.to_owned()
+    }
+}
+
+fn percent(value: i32, total: i32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * f64::from(value) / f64::from(total)
+    }
+}
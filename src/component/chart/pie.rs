@@ -0,0 +1,88 @@
+use std::f64::consts::PI;
+
+use super::{percent, short_label, ChartRow};
+use crate::component::{Pure, PureComponent};
+use yew::prelude::*;
+
+pub type PieChart = Pure<PieChartImpl>;
+
+const RADIUS: f64 = 100.0;
+const CENTRE: f64 = 110.0;
+const LEGEND_X: f64 = 240.0;
+const LEGEND_ROW: f64 = 24.0;
+
+/// A slice per row, sized by its share of the total. Rows with nothing in them are left out.
+#[derive(Clone, Properties, PartialEq)]
+pub struct PieChartImpl {
+    pub rows: Vec<ChartRow>,
+}
+
+/// The point on the edge of the pie `fraction` of the way round, starting from the top.
+fn point(fraction: f64) -> (f64, f64) {
+    let angle = 2.0 * PI * fraction - PI / 2.0;
+    (CENTRE + RADIUS * angle.cos(), CENTRE + RADIUS * angle.sin())
+}
+
+fn slice(start: f64, end: f64) -> String {
+    let (x1, y1) = point(start);
+    let (x2, y2) = point(end);
+    let large_arc = if end - start > 0.5 { 1 } else { 0 };
+    format!(
+        "M {c} {c} L {:.3} {:.3} A {r} {r} 0 {} 1 {:.3} {:.3} Z",
+        x1,
+        y1,
+        large_arc,
+        x2,
+        y2,
+        c = CENTRE,
+        r = RADIUS
+    )
+}
+
+impl PureComponent for PieChartImpl {
+    fn view(&self) -> Html {
+        let rows: Vec<&ChartRow> = self.rows.iter().filter(|row| row.total() > 0).collect();
+        let total: i32 = rows.iter().map(|row| row.total()).sum();
+        if total == 0 {
+            return html!(<p class="has-text-grey has-text-centered">{"No dots to chart yet."}</p>);
+        }
+        let height = (rows.len() as f64 * LEGEND_ROW).max(2.0 * CENTRE);
+
+        let mut start = 0.0;
+        let slices = rows
+            .iter()
+            .map(|row| {
+                let fraction = f64::from(row.total()) / f64::from(total);
+                let tooltip = html!(<title>{format!("{}: {} ({:.1}%)", row.label, row.total(), 100.0 * fraction)}</title>);
+                // An arc can't start and end at the same point, so a full pie is a circle
+                let shape = if rows.len() == 1 {
+                    html!(<circle cx=CENTRE cy=CENTRE r=RADIUS fill=row.colour>{tooltip}</circle>)
+                } else {
+                    html!(<path d=slice(start, start + fraction) fill=row.colour stroke="white">{tooltip}</path>)
+                };
+                start += fraction;
+                shape
+            })
+            .collect::<Html>();
+
+        let legend = rows.iter().enumerate().map(|(i, row)| {
+            let y = i as f64 * LEGEND_ROW + LEGEND_ROW / 2.0;
+            html! {
+                <g>
+                    <rect x=LEGEND_X y=y - 7.0 width="14" height="14" fill=row.colour />
+                    <text x=LEGEND_X + 22.0 y=y dominant-baseline="middle">
+                        <title>{&row.label}</title>
+                        {format!("{} - {} ({:.1}%)", short_label(&row.label), row.total(), percent(row.total(), total))}
+                    </text>
+                </g>
+            }
+        });
+
+        html! {
+            <svg viewBox=format!("0 0 600 {}", height) width="100%" role="img">
+                { slices }
+                { for legend }
+            </svg>
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::api::ApiError;
+use crate::component::{Panel, PanelBlock, PanelHeading, Pure, PureComponent};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+pub type ErrorPanel = Pure<ErrorPanelImpl>;
+pub type ErrorBlock = Pure<ErrorBlockImpl>;
+
+/// A whole panel standing in for content that failed to load.
+#[derive(Clone, Properties, PartialEq)]
+pub struct ErrorPanelImpl {
+    pub error: ApiError,
+    #[prop_or_default]
+    pub onretry: Option<Callback<MouseEvent>>,
+}
+
+/// A notification block explaining an error inside an existing panel.
+#[derive(Clone, Properties, PartialEq)]
+pub struct ErrorBlockImpl {
+    pub error: ApiError,
+    #[prop_or_default]
+    pub onretry: Option<Callback<MouseEvent>>,
+}
+
+fn heading(error: &ApiError) -> &'static str {
+    match error {
+        ApiError::Network(_) | ApiError::Timeout => "Can't reach the server",
+        ApiError::NotFound => "Poll not found",
+        ApiError::Forbidden { .. } => "Not allowed",
+        ApiError::Validation { .. } => "Something isn't right",
+        ApiError::RateLimited { .. } => "Slow down",
+        ApiError::Server { .. } => "Something went wrong",
+    }
+}
+
+impl PureComponent for ErrorPanelImpl {
+    fn view(&self) -> Html {
+        let action = if let ApiError::NotFound = self.error {
+            html!(
+                <PanelBlock>
+                    <RouterButton<crate::MountedRoute> route={crate::MountedRoute(crate::AppRoute::Index)} classes="button is-primary is-fullwidth">
+                        {"Create a New Poll"}
+                    </RouterButton<crate::MountedRoute>>
+                </PanelBlock>
+            )
+        } else {
+            html!()
+        };
+        html!(
+            <Panel>
+                <PanelHeading>
+                    <div class="level">
+                        <div class="level-left">
+                            <div class="level-item">
+                                {heading(&self.error)}
+                            </div>
+                        </div>
+                    </div>
+                </PanelHeading>
+                <ErrorBlock error=self.error.clone() onretry=self.onretry.clone() />
+                { action }
+            </Panel>
+        )
+    }
+}
+
+impl PureComponent for ErrorBlockImpl {
+    fn view(&self) -> Html {
+        let problems: Vec<String> = match &self.error {
+            ApiError::Validation { fields, .. } => fields
+                .iter()
+                .flat_map(|(field, problems)| {
+                    problems.iter().map(move |p| format!("{}: {}", field, p))
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let message = match &self.error {
+            ApiError::Validation { message, .. } => message
+                .clone()
+                .unwrap_or_else(|| "The server rejected the request.".into()),
+            error => error.to_string(),
+        };
+        let retry = match &self.onretry {
+            Some(onretry) if self.error.is_retryable() => html!(
+                <button class="button is-danger is-outlined is-small ml-3" onclick=onretry.clone()>
+                    {"Retry"}
+                </button>
+            ),
+            _ => html!(),
+        };
+        html!(
+            <PanelBlock class="notification is-danger is-light mb-0" style="display:block;">
+                <div class="level is-mobile">
+                    <div class="level-left">
+                        <div class="level-item">
+                            <p>{message}</p>
+                        </div>
+                    </div>
+                    <div class="level-right">
+                        <div class="level-item">
+                            { retry }
+                        </div>
+                    </div>
+                </div>
+                { if problems.is_empty() { html!() } else { html!(
+                    <ul>
+                        { for problems.iter().map(|p| html!(<li>{p}</li>)) }
+                    </ul>
+                ) } }
+            </PanelBlock>
+        )
+    }
+}
@@ -1,5 +1,9 @@
+mod chart;
+mod error;
 mod panel;
 mod pure;
 
+pub use chart::{BarChart, ChartRow, PieChart, Segment};
+pub use error::{ErrorBlock, ErrorPanel};
 pub use panel::{Panel, PanelBlock, PanelHeading};
 pub use pure::{Pure, PureComponent};
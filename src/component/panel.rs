@@ -23,14 +23,18 @@ pub struct PanelBlockImpl {
     pub children: Children,
     #[prop_or_default]
     pub class: Option<String>,
-    #[prop_or_else(|| "".into())]
+    #[prop_or_default]
     pub style: String,
-    #[prop_or_else(|| "div".into())]
+    #[prop_or_else(default_tag)]
     pub tag: String,
     #[prop_or_else(Callback::noop)]
     pub onclick: Callback<MouseEvent>,
 }
 
+fn default_tag() -> String {
+    "div".into()
+}
+
 impl PureComponent for PanelImpl {
     fn view(&self) -> Html {
         html! (
@@ -0,0 +1,148 @@
+//! Deployment settings, read at startup so a single build can be pointed at any backend.
+//!
+//! Settings are layered: the compile-time defaults below, then any `<meta>` tags in
+//! `index.html`, then a `config.json` served alongside it.
+//!
+//! ```html
+//! <meta name="dotdotyew:api-url" content="https://polls.example.com">
+//! <meta name="dotdotyew:base-path" content="/polls">
+//! <meta name="dotdotyew:config" content="/polls/config.json">
+//! ```
+//!
+//! ```json
+//! { "api_url": "https://polls.example.com", "base_path": "/polls" }
+//! ```
+use std::cell::RefCell;
+
+use serde::Deserialize;
+use yew::format::{Nothing, Text};
+use yew::prelude::*;
+use yew::services::fetch::{FetchTask, Request, Response};
+use yew::services::FetchService;
+
+#[cfg(debug_assertions)]
+const DEFAULT_API_URL: &str = "http://localhost:8000";
+#[cfg(not(debug_assertions))]
+const DEFAULT_API_URL: &str = "https://www.dotdotvote.com";
+
+const DEFAULT_BASE_PATH: &str = "/dotdotyew";
+
+thread_local! {
+    static CONFIG: RefCell<Config> = RefCell::new(Config::default());
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Where the dotdotvote API lives, without a trailing slash.
+    pub api_url: String,
+    /// The path the app is served under, without a trailing slash.
+    pub base_path: String,
+    /// Where to look for `config.json`.
+    pub config_url: Option<String>,
+}
+
+/// The contents of `config.json`. Anything left out keeps its current value.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ConfigFile {
+    pub api_url: Option<String>,
+    pub base_path: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            api_url: DEFAULT_API_URL.into(),
+            base_path: DEFAULT_BASE_PATH.into(),
+            config_url: None,
+        }
+    }
+}
+
+impl Config {
+    /// The compile-time defaults, overridden by any `<meta name="dotdotyew:...">` tags.
+    pub fn from_meta() -> Self {
+        let mut config = Config::default();
+        if let Some(api_url) = meta("dotdotyew:api-url") {
+            config.api_url = normalize_url(&api_url);
+        }
+        if let Some(base_path) = meta("dotdotyew:base-path") {
+            config.base_path = normalize_path(&base_path);
+        }
+        config.config_url = meta("dotdotyew:config");
+        config
+    }
+
+    pub fn merge(mut self, file: ConfigFile) -> Self {
+        if let Some(api_url) = file.api_url {
+            self.api_url = normalize_url(&api_url);
+        }
+        if let Some(base_path) = file.base_path {
+            self.base_path = normalize_path(&base_path);
+        }
+        self
+    }
+
+    fn config_url(&self) -> String {
+        self.config_url
+            .clone()
+            .unwrap_or_else(|| format!("{}/config.json", self.base_path))
+    }
+}
+
+fn meta(name: &str) -> Option<String> {
+    web_sys::window()?
+        .document()?
+        .query_selector(&format!("meta[name='{}']", name))
+        .ok()??
+        .get_attribute("content")
+}
+
+fn normalize_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_owned()
+}
+
+fn normalize_path(path: &str) -> String {
+    let path = path.trim().trim_matches('/');
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", path)
+    }
+}
+
+pub fn set(config: Config) {
+    CONFIG.with(|c| *c.borrow_mut() = config);
+}
+
+pub fn get() -> Config {
+    CONFIG.with(|c| c.borrow().clone())
+}
+
+pub fn api_url() -> String {
+    CONFIG.with(|c| c.borrow().api_url.clone())
+}
+
+pub fn base_path() -> String {
+    CONFIG.with(|c| c.borrow().base_path.clone())
+}
+
+/// Fetches `config.json` and hands back the current config with it applied. A missing or
+/// unreadable file isn't an error, the current config is simply kept.
+pub fn fetch<C, M, F>(link: &ComponentLink<C>, callback: F) -> Option<FetchTask>
+where
+    C: Component,
+    M: Into<C::Message>,
+    F: Fn(Config) -> M + 'static,
+{
+    let request = Request::get(get().config_url()).body(Nothing).ok()?;
+    let callback = link.callback(move |response: Response<Text>| {
+        let (meta, body) = response.into_parts();
+        let file = match body {
+            Ok(body) if meta.status.is_success() => serde_json::from_str(&body).ok(),
+            _ => None,
+        };
+        callback(get().merge(file.unwrap_or_default()))
+    });
+    FetchService::fetch(request, callback).ok()
+}
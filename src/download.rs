@@ -0,0 +1,57 @@
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+/// How long the Blob URL outlives the click. Some browsers start the download after `click()`
+/// returns, and fail it if the URL is already gone.
+const REVOKE_AFTER_MS: i32 = 10_000;
+
+/// Hands `contents` to the browser as a file download called `filename`, by clicking a
+/// temporary link to a Blob URL.
+pub fn download(filename: &str, mime: &str, contents: &str) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let document = window
+        .document()
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let body = document
+        .body()
+        .ok_or_else(|| JsValue::from_str("no body"))?;
+
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let link: HtmlAnchorElement = document.create_element("a")?.unchecked_into();
+    link.set_href(&url);
+    link.set_download(filename);
+    // Firefox ignores clicks on links that aren't in the document
+    body.append_child(&link)?;
+    link.click();
+    body.remove_child(&link)?;
+
+    let revoke = Closure::once_into_js(move || {
+        let _ = Url::revoke_object_url(&url);
+    });
+    window.set_timeout_with_callback_and_timeout_and_arguments_0(
+        revoke.unchecked_ref(),
+        REVOKE_AFTER_MS,
+    )?;
+    Ok(())
+}
+
+/// A file name for `title`, keeping letters and digits and joining the rest with dashes.
+pub fn filename(title: &str, suffix: &str) -> String {
+    let slug = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        format!("poll{}", suffix)
+    } else {
+        format!("{}{}", slug, suffix)
+    }
+}
@@ -2,36 +2,86 @@
 
 use wasm_bindgen::prelude::*;
 use yew::prelude::*;
+use yew::services::fetch::FetchTask;
 use yew_router::prelude::*;
+use yew_router::switch::Switch;
 
 pub mod api;
 pub mod component;
+pub mod config;
+pub mod download;
 pub mod poll;
-use poll::{CreatePoll, PollResults, ShowPoll};
+pub mod time;
+use poll::{CreatePoll, MyPolls, PollAdmin, PollResults, ResultsSort, ShowPoll};
 
+/// Routes relative to the configured base path, see `MountedRoute`.
 #[derive(Switch, Debug, Clone)]
 pub enum AppRoute {
-    #[to = "/dotdotyew/poll/{id}/results"]
+    #[to = "/poll/{id}/results?sort={sort}"]
+    SortedPollResults(String, ResultsSort),
+    #[to = "/poll/{id}/results"]
     PollResults(String),
-    #[to = "/dotdotyew/poll/{id}"]
+    #[to = "/polls"]
+    MyPolls,
+    #[to = "/poll/{id}/admin/{token}"]
+    PollAdmin(String, String),
+    #[to = "/poll/{id}/invite/{token}"]
+    PollInvite(String, String),
+    #[to = "/poll/{id}"]
     Poll(String),
-    #[to = "/dotdotyew"]
+    #[to = "/"]
     Index,
 }
 
+/// An `AppRoute` mounted under `config::base_path()`, which is only known at runtime.
+#[derive(Debug, Clone)]
+pub struct MountedRoute(pub AppRoute);
+
+impl Switch for MountedRoute {
+    fn from_route_part<STATE>(part: String, state: Option<STATE>) -> (Option<Self>, Option<STATE>) {
+        let base_path = config::base_path();
+        let rest = match part.strip_prefix(base_path.as_str()) {
+            Some("") => "/",
+            Some(rest) if rest.starts_with('/') => rest,
+            _ => return (None, state),
+        };
+        let (route, state) = AppRoute::from_route_part(rest.to_owned(), state);
+        (route.map(MountedRoute), state)
+    }
+
+    fn build_route_section<STATE>(self, route: &mut String) -> Option<STATE> {
+        route.push_str(&config::base_path());
+        self.0.build_route_section(route)
+    }
+}
+
+impl From<AppRoute> for MountedRoute {
+    fn from(route: AppRoute) -> Self {
+        MountedRoute(route)
+    }
+}
+
 struct Layout {
-    _link: ComponentLink<Self>,
+    loaded: bool,
+    _task: Option<FetchTask>,
 }
 
 impl Component for Layout {
-    type Message = ();
+    type Message = config::Config;
     type Properties = ();
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        Self { _link: link }
+        // Routes depend on the base path, so hold off rendering until config.json is in
+        let task = config::fetch(&link, |config| config);
+        Self {
+            loaded: task.is_none(),
+            _task: task,
+        }
     }
 
-    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
-        false
+    fn update(&mut self, config: Self::Message) -> ShouldRender {
+        config::set(config);
+        self.loaded = true;
+        true
     }
 
     fn change(&mut self, _props: Self::Properties) -> ShouldRender {
@@ -39,13 +89,20 @@ impl Component for Layout {
     }
 
     fn view(&self) -> Html {
+        if !self.loaded {
+            return html!();
+        }
         html! {
             <section class="section">
                 <div class="container">
-                    <Router<AppRoute, ()>
-                        render = Router::render(|switch: AppRoute| {
-                            match switch {
+                    <Router<MountedRoute, ()>
+                        render = Router::render(|switch: MountedRoute| {
+                            match switch.0 {
+                                AppRoute::SortedPollResults(id, sort) => html!(<PollResults poll_id={id} sort={sort} />),
                                 AppRoute::PollResults(id) => html!(<PollResults poll_id={id} />),
+                                AppRoute::MyPolls => html!(<MyPolls/>),
+                                AppRoute::PollAdmin(id, token) => html!(<PollAdmin poll_id={id} token={token} />),
+                                AppRoute::PollInvite(id, token) => html!(<ShowPoll poll_id={id} invite={Some(token)} />),
                                 AppRoute::Poll(id) => html!(<ShowPoll poll_id={id} />),
                                 AppRoute::Index => html!(<CreatePoll/>),
                             }
@@ -59,5 +116,6 @@ impl Component for Layout {
 
 #[wasm_bindgen(start)]
 pub fn run_app() {
+    config::set(config::Config::from_meta());
     App::<Layout>::new().mount_to_body();
 }
@@ -0,0 +1,761 @@
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use super::saved;
+use crate::api;
+use crate::component::{ErrorBlock, ErrorPanel, Panel, PanelBlock, PanelHeading};
+use crate::time;
+use yew::prelude::*;
+use yew::services::storage::{Area, StorageService};
+use yew_router::agent::{RouteAgentDispatcher, RouteRequest};
+use yew_router::prelude::*;
+use yew_router::route::Route;
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub poll_id: String,
+    pub token: String,
+}
+
+struct State {
+    results: Option<api::PollResults>,
+    title: String,
+    // The choices being edited, ending with a blank one to add
+    choices: Vec<api::UpdateChoice>,
+    // The roster's invite links, fetched for polls that have one
+    invites: Vec<api::Invite>,
+    saving: bool,
+    saved: bool,
+    busy: bool,
+    error: Option<api::ApiError>,
+    action_error: Option<api::ApiError>,
+}
+
+pub enum Msg {
+    FetchSuccess(api::PollResults),
+    FetchFailed(api::ApiError),
+    InvitesLoaded(Vec<api::Invite>),
+    Retry,
+    UpdateTitle(String),
+    UpdateChoice(usize, String),
+    UpdateCost(usize, String),
+    UpdateCategory(usize, Option<usize>),
+    RemoveChoice(usize),
+    Save,
+    SaveSuccess(api::Poll),
+    Close,
+    Reopen,
+    Advance(api::PollPhase),
+    StatusChanged(api::Poll),
+    DeleteBallot(api::VoteId),
+    DeletePoll,
+    Deleted,
+    ActionFailed(api::ApiError),
+}
+
+/// Where the creator of a poll edits, closes or deletes it, reached with the poll's admin
+/// token.
+pub struct PollAdmin {
+    link: ComponentLink<Self>,
+    props: Props,
+    state: State,
+    storage: StorageService,
+    router: RouteAgentDispatcher<()>,
+    backend: Rc<dyn api::PollBackend>,
+    // One request of each kind at a time. Starting another drops, and so cancels, the last.
+    fetching: Option<api::ApiTask>,
+    fetching_invites: Option<api::ApiTask>,
+    saving: Option<api::ApiTask>,
+    acting: Option<api::ApiTask>,
+}
+
+impl Component for PollAdmin {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut storage = StorageService::new(Area::Local).expect("browser storage disabled");
+        // Remember the token, so the poll page offers creator actions in this browser too
+        saved::store_admin_token(
+            &mut storage,
+            &props.poll_id,
+            &api::AdminToken(props.token.clone()),
+        );
+        let backend = api::backend();
+        let task = Self::fetch(&backend, &props, &link);
+        Self {
+            link,
+            props,
+            state: State {
+                results: None,
+                title: "".into(),
+                choices: Vec::new(),
+                invites: Vec::new(),
+                saving: false,
+                saved: false,
+                busy: false,
+                error: None,
+                action_error: None,
+            },
+            storage,
+            router: RouteAgentDispatcher::new(),
+            backend,
+            fetching: Some(task),
+            fetching_invites: None,
+            saving: None,
+            acting: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::FetchSuccess(results) => {
+                self.state.busy = false;
+                saved::remember_poll(&mut self.storage, &results.poll.uuid, &results.poll.title);
+                self.edit(&results.poll, &results.choices);
+                if results.poll.invite_only && self.state.invites.is_empty() {
+                    let backend = self.backend.clone();
+                    let (poll_id, token) = self.ids();
+                    let task = api::spawn(&self.link, async move {
+                        match backend.get_invites(&poll_id, &token).await {
+                            Ok(invites) => Msg::InvitesLoaded(invites),
+                            Err(error) => Msg::ActionFailed(error),
+                        }
+                    });
+                    self.fetching_invites = Some(task);
+                }
+                self.state.results = Some(results);
+                true
+            }
+            Msg::FetchFailed(error) => {
+                self.state.error = Some(error);
+                true
+            }
+            Msg::InvitesLoaded(invites) => {
+                self.state.invites = invites;
+                true
+            }
+            Msg::Retry => {
+                self.state.error = None;
+                let task = Self::fetch(&self.backend, &self.props, &self.link);
+                self.fetching = Some(task);
+                true
+            }
+            Msg::UpdateTitle(value) => {
+                self.state.title = value;
+                self.state.saved = false;
+                true
+            }
+            Msg::UpdateChoice(i, value) => {
+                self.state.choices[i].details = value;
+                if i == self.state.choices.len() - 1 {
+                    self.state.choices.push(api::UpdateChoice::default());
+                }
+                self.state.saved = false;
+                true
+            }
+            Msg::UpdateCost(i, value) => {
+                self.state.choices[i].cost = value.parse::<i32>().ok().map(|v| v.max(0));
+                self.state.saved = false;
+                true
+            }
+            Msg::UpdateCategory(i, category) => {
+                self.state.choices[i].category = category;
+                self.state.saved = false;
+                true
+            }
+            Msg::RemoveChoice(i) => {
+                self.state.choices.remove(i);
+                self.state.saved = false;
+                true
+            }
+            Msg::Save => {
+                let update = api::UpdatePoll {
+                    title: self.state.title.clone(),
+                    choices: self.state.choices.clone(),
+                };
+                let backend = self.backend.clone();
+                let (poll_id, token) = self.ids();
+                let task = api::spawn(&self.link, async move {
+                    match backend.update_poll(&poll_id, &token, &update).await {
+                        Ok(poll) => Msg::SaveSuccess(poll),
+                        Err(error) => Msg::ActionFailed(error),
+                    }
+                });
+                self.state.saving = true;
+                self.state.action_error = None;
+                self.saving = Some(task);
+                true
+            }
+            Msg::SaveSuccess(poll) => {
+                self.state.saving = false;
+                self.state.saved = true;
+                saved::remember_poll(&mut self.storage, &poll.poll.uuid, &poll.poll.title);
+                self.edit(&poll.poll, &poll.choices);
+                if let Some(results) = &mut self.state.results {
+                    results.votes.retain(|vote| {
+                        poll.choices
+                            .iter()
+                            .any(|choice| choice.id == vote.choice_id)
+                    });
+                    results.poll = poll.poll;
+                    results.choices = poll.choices;
+                }
+                true
+            }
+            Msg::Close | Msg::Reopen => {
+                let close = matches!(msg, Msg::Close);
+                let backend = self.backend.clone();
+                let (poll_id, token) = self.ids();
+                let task = api::spawn(&self.link, async move {
+                    let poll = if close {
+                        backend.close_poll(&poll_id, &token).await
+                    } else {
+                        backend.reopen_poll(&poll_id, &token).await
+                    };
+                    match poll {
+                        Ok(poll) => Msg::StatusChanged(poll),
+                        Err(error) => Msg::ActionFailed(error),
+                    }
+                });
+                self.state.busy = true;
+                self.state.action_error = None;
+                self.acting = Some(task);
+                true
+            }
+            Msg::Advance(phase) => {
+                let advance = api::AdvancePhase {
+                    phase,
+                    merges: Vec::new(),
+                };
+                let backend = self.backend.clone();
+                let (poll_id, token) = self.ids();
+                let task = api::spawn(&self.link, async move {
+                    match backend.advance_phase(&poll_id, &token, &advance).await {
+                        Ok(poll) => Msg::StatusChanged(poll),
+                        Err(error) => Msg::ActionFailed(error),
+                    }
+                });
+                self.state.busy = true;
+                self.state.action_error = None;
+                self.acting = Some(task);
+                true
+            }
+            Msg::StatusChanged(poll) => {
+                self.state.busy = false;
+                if let Some(results) = &mut self.state.results {
+                    results.poll = poll.poll;
+                }
+                true
+            }
+            Msg::DeleteBallot(vote) => {
+                let backend = self.backend.clone();
+                let (poll_id, token) = self.ids();
+                let task = api::spawn(&self.link, async move {
+                    if let Err(error) = backend.delete_ballot(&poll_id, &token, vote).await {
+                        return Msg::ActionFailed(error);
+                    }
+                    match backend.get_admin(&poll_id, &token).await {
+                        Ok(results) => Msg::FetchSuccess(results),
+                        Err(error) => Msg::ActionFailed(error),
+                    }
+                });
+                self.state.busy = true;
+                self.state.action_error = None;
+                self.acting = Some(task);
+                true
+            }
+            Msg::DeletePoll => {
+                let confirmed = web_sys::window()
+                    .and_then(|window| {
+                        window
+                            .confirm_with_message(
+                                "Delete this poll and every vote on it? This can't be undone.",
+                            )
+                            .ok()
+                    })
+                    .unwrap_or(false);
+                if !confirmed {
+                    return false;
+                }
+                let backend = self.backend.clone();
+                let (poll_id, token) = self.ids();
+                let task = api::spawn(&self.link, async move {
+                    match backend.delete_poll(&poll_id, &token).await {
+                        Ok(()) => Msg::Deleted,
+                        Err(error) => Msg::ActionFailed(error),
+                    }
+                });
+                self.state.busy = true;
+                self.state.action_error = None;
+                self.acting = Some(task);
+                true
+            }
+            Msg::Deleted => {
+                saved::forget_poll(&mut self.storage, &self.props.poll_id);
+                self.router
+                    .send(RouteRequest::ChangeRoute(Route::from(crate::MountedRoute(
+                        crate::AppRoute::Index,
+                    ))));
+                false
+            }
+            Msg::ActionFailed(error) => {
+                self.state.saving = false;
+                self.state.busy = false;
+                self.state.action_error = Some(error);
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            self.state.results = None;
+            self.state.invites.clear();
+            self.fetching = Some(Self::fetch(&self.backend, &self.props, &self.link));
+            self.fetching_invites = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        if let Some(error) = &self.state.error {
+            html!(<ErrorPanel error=error.clone() onretry=self.link.callback(|_| Msg::Retry) />)
+        } else if let Some(results) = &self.state.results {
+            html!(
+                <>
+                    { self.show_details(results) }
+                    { self.show_status(results) }
+                    { self.show_invites(results) }
+                    { self.show_ballots(results) }
+                </>
+            )
+        } else {
+            html!(
+                <Panel>
+                    <PanelHeading/>
+                </Panel>
+            )
+        }
+    }
+}
+
+impl PollAdmin {
+    fn fetch(
+        backend: &Rc<dyn api::PollBackend>,
+        props: &Props,
+        link: &ComponentLink<Self>,
+    ) -> api::ApiTask {
+        let backend = backend.clone();
+        let poll_id = api::PollUuid::from(props.poll_id.clone());
+        let token = api::AdminToken(props.token.clone());
+        api::spawn(link, async move {
+            match backend.get_admin(&poll_id, &token).await {
+                Ok(results) => Msg::FetchSuccess(results),
+                Err(error) => Msg::FetchFailed(error),
+            }
+        })
+    }
+
+    fn ids(&self) -> (api::PollUuid, api::AdminToken) {
+        (
+            api::PollUuid::from(self.props.poll_id.clone()),
+            api::AdminToken(self.props.token.clone()),
+        )
+    }
+
+    /// Starts editing from the poll as it is now.
+    fn edit(&mut self, poll: &api::PollMetadata, choices: &[api::PollChoice]) {
+        self.state.title = poll.title.clone();
+        self.state.choices = choices
+            .iter()
+            .map(|choice| api::UpdateChoice {
+                id: Some(choice.id),
+                details: choice.details.clone(),
+                cost: choice.cost,
+                category: choice.category,
+            })
+            .chain(Some(api::UpdateChoice::default()))
+            .collect();
+    }
+
+    /// Validation problems the server reported for `field`.
+    fn problems(&self, field: &str) -> Option<&Vec<String>> {
+        match &self.state.action_error {
+            Some(api::ApiError::Validation { fields, .. }) => fields.get(field),
+            _ => None,
+        }
+    }
+
+    fn field_errors(&self, field: &str) -> Html {
+        match self.problems(field) {
+            Some(problems) => html!(<p class="help is-danger">{problems.join(", ")}</p>),
+            None => html!(),
+        }
+    }
+
+    fn route(&self, route: crate::AppRoute, label: &str, icon: &str) -> Html {
+        html!(
+            <RouterButton<crate::MountedRoute> route={crate::MountedRoute(route)} classes="button is-light">
+                <span class="icon"><i class=icon></i></span>
+                <span>{label}</span>
+            </RouterButton<crate::MountedRoute>>
+        )
+    }
+
+    fn show_details(&self, results: &api::PollResults) -> Html {
+        let funded = results.poll.funding.is_some();
+        let save_class = if self.state.saving {
+            "button is-primary is-loading"
+        } else {
+            "button is-primary"
+        };
+        let listed = self
+            .state
+            .choices
+            .iter()
+            .filter(|choice| !choice.details.trim().is_empty())
+            .count();
+        let removed: usize = results
+            .choices
+            .iter()
+            .filter(|choice| {
+                !self
+                    .state
+                    .choices
+                    .iter()
+                    .any(|c| c.id == Some(choice.id) && !c.details.trim().is_empty())
+            })
+            .map(|choice| {
+                results
+                    .votes
+                    .iter()
+                    .filter(|vote| vote.choice_id == choice.id)
+                    .count()
+            })
+            .sum();
+        html!(
+            <Panel>
+                <PanelHeading>
+                    <div class="level">
+                        <div class="level-left">
+                            <div class="level-item">
+                                {format!("Manage \"{}\"", results.poll.title)}
+                            </div>
+                        </div>
+                        <div class="level-right">
+                            <div class="level-item buttons">
+                                { self.route(crate::AppRoute::Poll(self.props.poll_id.clone()), "Poll", "fas fa-vote-yea") }
+                                { self.route(crate::AppRoute::PollResults(self.props.poll_id.clone()), "Results", "fas fa-chart-bar") }
+                            </div>
+                        </div>
+                    </div>
+                </PanelHeading>
+                <PanelBlock>
+                    <form class="control">
+                        <div class="field">
+                            <label class="label">{"Title"}</label>
+                            <div class="control">
+                                <input class="input" type="text" value=&self.state.title
+                                    oninput=self.link.callback(|e: InputData| Msg::UpdateTitle(e.value)) />
+                            </div>
+                            { self.field_errors("title") }
+                        </div>
+                        <label class="label">{"Choices"}</label>
+                        { for self.state.choices.iter().enumerate().map(|(i, choice)| self.view_choice(i, choice, funded, &results.poll.categories)) }
+                        { self.field_errors("choices") }
+                        { self.field_errors("costs") }
+                        { self.field_errors("categories") }
+                        { if removed > 0 {
+                            html!(<p class="help is-warning">{format!(
+                                "Removing choices also removes the {} {} placed on them.",
+                                removed,
+                                if removed == 1 { "vote" } else { "votes" }
+                            )}</p>)
+                        } else {
+                            html!()
+                        } }
+                        <div class="field is-grouped is-grouped-right mt-3">
+                            { if self.state.saved {
+                                html!(<p class="control"><span class="tag is-success is-light is-medium">{"Saved"}</span></p>)
+                            } else {
+                                html!()
+                            } }
+                            <p class="control">
+                                <a class=save_class disabled={self.state.title.trim().is_empty() || listed < 2}
+                                    onclick=self.link.callback(|_| Msg::Save)>
+                                    {"Save changes"}
+                                </a>
+                            </p>
+                        </div>
+                    </form>
+                </PanelBlock>
+                { if let Some(error) = &self.state.action_error {
+                    html!(<ErrorBlock error=error.clone() />)
+                } else {
+                    html!()
+                } }
+            </Panel>
+        )
+    }
+
+    fn view_choice(
+        &self,
+        i: usize,
+        choice: &api::UpdateChoice,
+        funded: bool,
+        categories: &[api::Category],
+    ) -> Html {
+        let last = i == self.state.choices.len() - 1;
+        let placeholder = if last { "Add a choice..." } else { "" };
+        html!(
+            <div class="field has-addons">
+                <div class="control is-expanded">
+                    <input class="input" type="text" placeholder=placeholder value=&choice.details
+                        oninput=self.link.callback(move |e: InputData| Msg::UpdateChoice(i, e.value)) />
+                </div>
+                { if funded {
+                    html!(
+                        <div class="control">
+                            <input class="input" type="number" min="0" placeholder="Cost" style="width:8em;"
+                                value=choice.cost.map(|cost| cost.to_string()).unwrap_or_default()
+                                oninput=self.link.callback(move |e: InputData| Msg::UpdateCost(i, e.value)) />
+                        </div>
+                    )
+                } else {
+                    html!()
+                } }
+                { if categories.is_empty() {
+                    html!()
+                } else {
+                    html!(
+                        <div class="control">
+                            <span class="select">
+                                <select onchange=self.link.callback(move |e: ChangeData| match e {
+                                    ChangeData::Select(select) => Msg::UpdateCategory(
+                                        i,
+                                        usize::try_from(select.selected_index() - 1).ok(),
+                                    ),
+                                    _ => Msg::UpdateCategory(i, None),
+                                })>
+                                    <option selected={choice.category.is_none()}>{"No category"}</option>
+                                    { for categories.iter().enumerate().map(|(index, category)| html!(
+                                        <option selected={choice.category == Some(index)}>{&category.name}</option>
+                                    )) }
+                                </select>
+                            </span>
+                        </div>
+                    )
+                } }
+                { if last {
+                    html!()
+                } else {
+                    html!(
+                        <div class="control">
+                            <button class="button is-danger is-light" type="button" title="Remove choice"
+                                onclick=self.link.callback(move |_| Msg::RemoveChoice(i))>
+                                <span class="icon"><i class="fas fa-times"></i></span>
+                            </button>
+                        </div>
+                    )
+                } }
+            </div>
+        )
+    }
+
+    fn show_status(&self, results: &api::PollResults) -> Html {
+        let status = results.poll.status(time::now());
+        let message = match (status, results.poll.opens_at, results.poll.closes_at) {
+            _ if results.poll.collecting() => "Collecting choices from participants.".into(),
+            _ if results.poll.phase == Some(api::PollPhase::Reveal) => {
+                "Voting has ended and the results are revealed.".into()
+            }
+            (api::PollStatus::Scheduled, Some(opens_at), _) => {
+                format!("Voting opens on {}.", time::format(opens_at))
+            }
+            (api::PollStatus::Closed, _, Some(closes_at)) => {
+                format!("Voting closed on {}.", time::format(closes_at))
+            }
+            (_, _, Some(closes_at)) => format!("Voting closes on {}.", time::format(closes_at)),
+            _ => "Voting is open.".into(),
+        };
+        let loading = if self.state.busy { " is-loading" } else { "" };
+        html!(
+            <Panel>
+                <PanelHeading>{"Voting"}</PanelHeading>
+                <PanelBlock>
+                    <div class="level" style="width:100%;">
+                        <div class="level-left">
+                            <div class="level-item">{message}</div>
+                        </div>
+                        <div class="level-right">
+                            <div class="level-item buttons">
+                                { match results.poll.phase.and_then(api::PollPhase::next) {
+                                    Some(next) => html!(
+                                        <button class=format!("button is-primary is-outlined{}", loading)
+                                            disabled=self.state.busy onclick=self.link.callback(move |_| Msg::Advance(next))>
+                                            <span class="icon"><i class=if next == api::PollPhase::Vote { "fas fa-vote-yea" } else { "fas fa-eye" }></i></span>
+                                            <span>{if next == api::PollPhase::Vote { "Start voting" } else { "Reveal results" }}</span>
+                                        </button>
+                                    ),
+                                    None => html!(),
+                                } }
+                                { if results.poll.phase == Some(api::PollPhase::Reveal) {
+                                    html!()
+                                } else if status == api::PollStatus::Closed {
+                                    html!(
+                                        <button class=format!("button is-success is-outlined{}", loading)
+                                            disabled=self.state.busy onclick=self.link.callback(|_| Msg::Reopen)>
+                                            <span class="icon"><i class="fas fa-lock-open"></i></span>
+                                            <span>{"Reopen"}</span>
+                                        </button>
+                                    )
+                                } else {
+                                    html!(
+                                        <button class=format!("button is-warning is-outlined{}", loading)
+                                            disabled=self.state.busy onclick=self.link.callback(|_| Msg::Close)>
+                                            <span class="icon"><i class="fas fa-lock"></i></span>
+                                            <span>{"Close now"}</span>
+                                        </button>
+                                    )
+                                } }
+                                <button class=format!("button is-danger{}", loading)
+                                    disabled=self.state.busy onclick=self.link.callback(|_| Msg::DeletePoll)>
+                                    <span class="icon"><i class="fas fa-trash"></i></span>
+                                    <span>{"Delete poll"}</span>
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                </PanelBlock>
+            </Panel>
+        )
+    }
+
+    /// A link for each person on the roster, and whether they've voted with it yet.
+    fn show_invites(&self, results: &api::PollResults) -> Html {
+        if !results.poll.invite_only {
+            return html!();
+        }
+        let voted = results
+            .participation
+            .iter()
+            .filter(|participant| participant.voted)
+            .count();
+        html!(
+            <Panel>
+                <PanelHeading>
+                    {format!("Invite links ({} of {} voted)", voted, results.participation.len())}
+                </PanelHeading>
+                <PanelBlock class="notification is-light mb-0">
+                    <p>{"Send each person their own link. It fills in their name, and only works once."}</p>
+                </PanelBlock>
+                { for self.state.invites.iter().map(|invite| {
+                    let voted = results
+                        .participation
+                        .iter()
+                        .any(|participant| participant.name == invite.name && participant.voted);
+                    html!(
+                        <PanelBlock>
+                            <div class="field has-addons" style="width:100%;">
+                                <p class="control">
+                                    <span class="button is-static" style="min-width:8em;">{&invite.name}</span>
+                                </p>
+                                <p class="control is-expanded">
+                                    <input class="input" type="text" readonly=true value=self.invite_url(invite) />
+                                </p>
+                                <p class="control">
+                                    { if voted {
+                                        html!(<span class="button is-static has-text-success">{"Voted"}</span>)
+                                    } else {
+                                        html!(<span class="button is-static">{"Not yet"}</span>)
+                                    } }
+                                </p>
+                            </div>
+                        </PanelBlock>
+                    )
+                }) }
+            </Panel>
+        )
+    }
+
+    /// The full address of an invite link, to send to whoever it's for.
+    fn invite_url(&self, invite: &api::Invite) -> String {
+        let route = Route::<()>::from(crate::MountedRoute(crate::AppRoute::PollInvite(
+            self.props.poll_id.clone(),
+            invite.token.0.clone(),
+        )));
+        let origin = web_sys::window()
+            .and_then(|window| window.location().origin().ok())
+            .unwrap_or_default();
+        format!("{}{}", origin, route.route)
+    }
+
+    /// Every ballot, newest first.
+    fn show_ballots(&self, results: &api::PollResults) -> Html {
+        let mut ballots: Vec<Vec<&api::Vote>> = Vec::new();
+        for vote in &results.votes {
+            match ballots
+                .iter_mut()
+                .find(|ballot| ballot[0].ballot == vote.ballot)
+            {
+                Some(ballot) => ballot.push(vote),
+                None => ballots.push(vec![vote]),
+            }
+        }
+        ballots.sort_by_key(|ballot| std::cmp::Reverse(ballot[0].created_at));
+        html!(
+            <Panel>
+                <PanelHeading>{format!("Ballots ({})", ballots.len())}</PanelHeading>
+                { if ballots.is_empty() {
+                    html!(<PanelBlock><p class="has-text-grey">{"Nobody has voted yet."}</p></PanelBlock>)
+                } else {
+                    html!({ for ballots.iter().map(|ballot| self.view_ballot(results, ballot)) })
+                } }
+            </Panel>
+        )
+    }
+
+    fn view_ballot(&self, results: &api::PollResults, ballot: &[&api::Vote]) -> Html {
+        let first = ballot[0].id;
+        let summary = ballot
+            .iter()
+            .filter_map(|vote| {
+                let choice = results.choices.iter().find(|c| c.id == vote.choice_id)?;
+                Some(format!("{} ({})", choice.details, vote.dots))
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        html!(
+            <PanelBlock>
+                <div class="level" style="width:100%;">
+                    <div class="level-left">
+                        <div class="level-item">
+                            <div>
+                                <p><strong>{&ballot[0].voter}</strong>{" "}
+                                    { if results.poll.anonymous {
+                                        html!()
+                                    } else {
+                                        html!(<span class="has-text-grey is-size-7">{time::format(ballot[0].created_at)}</span>)
+                                    } }</p>
+                                <p class="is-size-7">{summary}</p>
+                            </div>
+                        </div>
+                    </div>
+                    <div class="level-right">
+                        <div class="level-item">
+                            <button class="button is-small is-danger is-light" disabled=self.state.busy
+                                title="Delete this ballot"
+                                onclick=self.link.callback(move |_| Msg::DeleteBallot(first))>
+                                <span class="icon"><i class="fas fa-trash"></i></span>
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            </PanelBlock>
+        )
+    }
+}
@@ -1,11 +1,73 @@
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use super::saved;
 use crate::api;
-use crate::component::{Panel, PanelBlock, PanelHeading};
+use crate::component::{ErrorBlock, Panel, PanelBlock, PanelHeading};
+use crate::time;
 use serde::{Deserialize, Serialize};
-use yew::format::Json;
 use yew::prelude::*;
-use yew::services::fetch::FetchTask;
+use yew::services::storage::{Area, StorageService};
 use yew_router::prelude::*;
 
+const VOTING_METHODS: [(api::VotingMethod, &str, &str); 6] = [
+    (
+        api::VotingMethod::Dots,
+        "Dot voting",
+        "Voters spread a number of dots over the choices they like.",
+    ),
+    (
+        api::VotingMethod::Quadratic,
+        "Quadratic",
+        "Voters spend credits on dots, where n dots on one choice cost n\u{b2} credits.",
+    ),
+    (
+        api::VotingMethod::Approval,
+        "Approval",
+        "Voters tick every choice they'd be happy with.",
+    ),
+    (
+        api::VotingMethod::Score,
+        "Score",
+        "Voters score every choice from 0 to 5, the highest total wins.",
+    ),
+    (
+        api::VotingMethod::RankedChoice,
+        "Ranked choice",
+        "Voters rank the choices. Last place is knocked out round by round until one choice has a majority.",
+    ),
+    (
+        api::VotingMethod::Borda,
+        "Borda count",
+        "Voters rank the choices, which earn more points the higher they're ranked.",
+    ),
+];
+
+const RESULTS_VISIBILITIES: [(api::ResultsVisibility, &str, &str); 4] = [
+    (
+        api::ResultsVisibility::Always,
+        "Always",
+        "Anyone with the link can see the results as votes come in.",
+    ),
+    (
+        api::ResultsVisibility::AfterVote,
+        "After voting",
+        "Voters see the results once they've voted, and everyone else once voting closes.",
+    ),
+    (
+        api::ResultsVisibility::AfterClose,
+        "After closing",
+        "Nobody but you sees the results until voting closes.",
+    ),
+    (
+        api::ResultsVisibility::CreatorOnly,
+        "Only me",
+        "Only you can see the results, from this browser or the poll's admin page.",
+    ),
+];
+
+const QUADRATIC_CREDITS: i32 = 16;
+
 const ANSWER_SUGGESTIONS: [&str; 7] = [
     "Sunday",
     "Monday",
@@ -20,22 +82,61 @@ const ANSWER_SUGGESTIONS: [&str; 7] = [
 struct State {
     title: String,
     choices: Vec<String>,
+    voting_method: api::VotingMethod,
+    dot_budget: api::DotBudget,
+    rules: api::VoteRules,
+    // Matched up with `choices` by index
+    costs: Vec<Option<i32>>,
+    funding: Option<i32>,
+    opens_at: Option<api::Timestamp>,
+    closes_at: Option<api::Timestamp>,
+    results_visibility: api::ResultsVisibility,
+    anonymous: bool,
+    // One name per line
+    roster: String,
+    brainstorm: bool,
+    // Ends in a blank entry to add the next category with
+    categories: Vec<api::Category>,
+    // Matched up with `choices` by index
+    choice_categories: Vec<Option<usize>>,
     loading: bool,
+    #[serde(skip)]
+    error: Option<api::ApiError>,
 }
 
 pub enum Msg {
     UpdateTitle(String),
     UpdateChoice(usize, String),
+    UpdateCost(usize, String),
+    UpdateFunding(String),
+    UpdateOpensAt(String),
+    UpdateClosesAt(String),
+    UpdateVotingMethod(usize),
+    UpdateResultsVisibility(usize),
+    ToggleAnonymous,
+    UpdateRoster(String),
+    UpdateBudgetMode(String),
+    UpdateBudgetValue(String),
+    UpdateMaxPerChoice(String),
+    UpdateMinChoices(String),
+    ToggleNoStacking,
+    UpdateNegativeDots(String),
+    ToggleBrainstorm,
+    UpdateCategory(usize, String),
+    UpdateCategoryDots(usize, String),
+    UpdateChoiceCategory(usize, Option<usize>),
     Submit,
     PostSuccess(api::CreatePollResponse),
-    PostFailed,
+    PostFailed(api::ApiError),
 }
 
 pub struct CreatePoll {
     link: ComponentLink<Self>,
     state: State,
+    storage: StorageService,
     router: RouteAgentDispatcher<()>,
-    tasks: Vec<FetchTask>,
+    backend: Rc<dyn api::PollBackend>,
+    tasks: Vec<api::ApiTask>,
 }
 
 impl Component for CreatePoll {
@@ -48,9 +149,25 @@ impl Component for CreatePoll {
             state: State {
                 title: "".into(),
                 choices: vec!["".into(); 3],
+                voting_method: api::VotingMethod::default(),
+                dot_budget: api::DotBudget::default(),
+                rules: api::VoteRules::default(),
+                costs: vec![None; 3],
+                funding: None,
+                opens_at: None,
+                closes_at: None,
+                results_visibility: api::ResultsVisibility::default(),
+                anonymous: false,
+                roster: "".into(),
+                brainstorm: false,
+                categories: vec![api::Category::default()],
+                choice_categories: vec![None; 3],
                 loading: false,
+                error: None,
             },
+            storage: StorageService::new(Area::Local).expect("browser storage disabled"),
             router: RouteAgentDispatcher::new(),
+            backend: api::backend(),
             tasks: Vec::new(),
         }
     }
@@ -65,36 +182,166 @@ impl Component for CreatePoll {
                 self.state.choices[i] = value;
                 if i == self.state.choices.len() - 1 {
                     self.state.choices.push("".to_owned());
+                    self.state.costs.push(None);
+                    self.state.choice_categories.push(None);
                 }
                 true
             }
+            Msg::UpdateCost(i, value) => {
+                self.state.costs[i] = value.parse::<i32>().ok().map(|v| v.max(0));
+                true
+            }
+            Msg::UpdateFunding(value) => {
+                self.state.funding = value.parse::<i32>().ok().map(|v| v.max(1));
+                true
+            }
+            Msg::UpdateOpensAt(value) => {
+                self.state.opens_at = time::parse_input(&value);
+                true
+            }
+            Msg::UpdateClosesAt(value) => {
+                self.state.closes_at = time::parse_input(&value);
+                true
+            }
+            Msg::UpdateVotingMethod(i) => {
+                self.state.voting_method = VOTING_METHODS[i].0;
+                // Two credits only buy one dot, so start quadratic polls with something usable
+                if self.state.voting_method == api::VotingMethod::Quadratic
+                    && self.state.dot_budget == api::DotBudget::default()
+                {
+                    self.state.dot_budget = api::DotBudget::Fixed {
+                        dots: QUADRATIC_CREDITS,
+                    };
+                }
+                true
+            }
+            Msg::UpdateResultsVisibility(i) => {
+                self.state.results_visibility = RESULTS_VISIBILITIES[i].0;
+                true
+            }
+            Msg::ToggleAnonymous => {
+                self.state.anonymous = !self.state.anonymous;
+                true
+            }
+            Msg::UpdateRoster(value) => {
+                self.state.roster = value;
+                true
+            }
+            Msg::UpdateBudgetMode(mode) => {
+                let value = self.budget_value();
+                self.state.dot_budget = match mode.as_str() {
+                    "scaled" => api::DotBudget::Scaled {
+                        choices_per_dot: value,
+                    },
+                    _ => api::DotBudget::Fixed { dots: value },
+                };
+                true
+            }
+            Msg::UpdateBudgetValue(value) => {
+                let value = value.parse::<i32>().unwrap_or(1).max(1);
+                match &mut self.state.dot_budget {
+                    api::DotBudget::Fixed { dots } => *dots = value,
+                    api::DotBudget::Scaled { choices_per_dot } => *choices_per_dot = value,
+                }
+                true
+            }
+            Msg::UpdateMaxPerChoice(value) => {
+                self.state.rules.max_per_choice = value.parse::<i32>().ok().map(|v| v.max(1));
+                true
+            }
+            Msg::UpdateMinChoices(value) => {
+                self.state.rules.min_choices = value.parse::<i32>().ok().map(|v| v.max(1));
+                true
+            }
+            Msg::ToggleNoStacking => {
+                self.state.rules.no_stacking = !self.state.rules.no_stacking;
+                true
+            }
+            Msg::UpdateNegativeDots(value) => {
+                self.state.rules.negative_dots = value.parse::<i32>().ok().filter(|v| *v > 0);
+                true
+            }
+            Msg::ToggleBrainstorm => {
+                self.state.brainstorm = !self.state.brainstorm;
+                true
+            }
+            Msg::UpdateCategory(i, value) => {
+                self.state.categories[i].name = value;
+                if i == self.state.categories.len() - 1 {
+                    self.state.categories.push(api::Category::default());
+                }
+                true
+            }
+            Msg::UpdateCategoryDots(i, value) => {
+                self.state.categories[i].dots = value.parse::<i32>().ok().map(|v| v.max(1));
+                true
+            }
+            Msg::UpdateChoiceCategory(i, category) => {
+                self.state.choice_categories[i] = category;
+                true
+            }
             Msg::Submit => {
                 self.state.loading = true;
+                self.state.error = None;
 
-                let task = api::create_poll(
-                    &self.state.title,
-                    &self.state.choices,
-                    &self.link,
-                    |response| {
-                        if let (meta, Json(Ok(body))) = response.into_parts() {
-                            if meta.status.is_success() {
-                                return Msg::PostSuccess(body);
-                            }
-                        }
-                        Msg::PostFailed
+                let backend = self.backend.clone();
+                let (categories, choice_categories) = self.categories();
+                let poll = api::CreatePoll {
+                    title: self.state.title.clone(),
+                    choices: self.state.choices.clone(),
+                    voting_method: self.state.voting_method,
+                    dot_budget: self.state.dot_budget,
+                    // The rules are only shown for dot voting, so don't send stale ones
+                    rules: match self.state.voting_method {
+                        api::VotingMethod::Dots => self.state.rules.clone(),
+                        _ => api::VoteRules::default(),
+                    },
+                    costs: match self.state.funding {
+                        Some(_) => self.state.costs.clone(),
+                        None => Vec::new(),
                     },
-                );
+                    funding: self.state.funding,
+                    opens_at: self.state.opens_at,
+                    closes_at: self.state.closes_at,
+                    brainstorm: self.state.brainstorm,
+                    categories,
+                    choice_categories,
+                    results_visibility: self.state.results_visibility,
+                    anonymous: self.state.anonymous,
+                    roster: self.state.roster.lines().map(str::to_owned).collect(),
+                };
+                let task = api::spawn(&self.link, async move {
+                    match backend.create_poll(&poll).await {
+                        Ok(body) => Msg::PostSuccess(body),
+                        Err(error) => Msg::PostFailed(error),
+                    }
+                });
                 self.tasks.push(task);
                 true
             }
             Msg::PostSuccess(response) => {
+                if let Some(token) = &response.admin_token {
+                    saved::store_admin_token(&mut self.storage, response.poll.as_str(), token);
+                }
+                saved::remember_poll(&mut self.storage, &response.poll, self.state.title.trim());
+                // With a roster, the creator's next step is handing out the invite links
+                let route = match response.admin_token {
+                    Some(token) if !response.invites.is_empty() => {
+                        crate::AppRoute::PollAdmin(response.poll.0, token.0)
+                    }
+                    _ => crate::AppRoute::Poll(response.poll.0),
+                };
                 self.router
                     .send(yew_router::agent::RouteRequest::ChangeRoute(
-                        yew_router::route::Route::from(crate::AppRoute::Poll(response.poll)),
+                        yew_router::route::Route::from(crate::MountedRoute(route)),
                     ));
                 false
             }
-            Msg::PostFailed => false,
+            Msg::PostFailed(error) => {
+                self.state.loading = false;
+                self.state.error = Some(error);
+                true
+            }
         }
     }
 
@@ -103,8 +350,9 @@ impl Component for CreatePoll {
     }
 
     fn view(&self) -> Html {
-        let valid_choices = self.state.choices.iter().filter(|s| s.len() > 0).count();
-        let can_submit = self.state.title.len() > 0 && valid_choices >= 2;
+        let valid_choices = self.valid_choices();
+        let can_submit =
+            !self.state.title.is_empty() && (self.state.brainstorm || valid_choices >= 2);
         let button_class = if self.state.loading {
             "button is-primary is-loading"
         } else {
@@ -119,6 +367,14 @@ impl Component for CreatePoll {
                                 {"Create a Dot Poll"}
                             </div>
                         </div>
+                        <div class="level-right">
+                            <div class="level-item">
+                                <RouterButton<crate::MountedRoute> route={crate::MountedRoute(crate::AppRoute::MyPolls)} classes="button is-light is-small">
+                                    <span class="icon"><i class="fas fa-history"></i></span>
+                                    <span>{"My polls"}</span>
+                                </RouterButton<crate::MountedRoute>>
+                            </div>
+                        </div>
                     </div>
                 </PanelHeading>
                 <PanelBlock>
@@ -130,15 +386,31 @@ impl Component for CreatePoll {
                             <div class="field-body">
                                 <div class="field">
                                     <div class="control">
-                                        <input class="input" type="text"
+                                        <input class=self.input_class("title") type="text"
                                             placeholder="Which day of the week should we select?..."
                                             value=&self.state.title oninput=self.link.callback(|e:
                                             InputData| Msg::UpdateTitle(e.value)) />
                                     </div>
+                                    { self.field_errors("title") }
                                 </div>
                             </div>
                         </div>
                         { for self.state.choices.iter().enumerate().map(|(i, _)| self.view_answer(i)) }
+                        { self.view_brainstorm() }
+                        { self.view_categories() }
+                        { self.view_voting_method() }
+                        { if self.state.voting_method.has_budget() { self.view_budget() } else { html!() } }
+                        { if self.state.voting_method == api::VotingMethod::Dots { self.view_rules() } else { html!() } }
+                        { self.view_funding() }
+                        { self.view_schedule() }
+                        { self.view_results_visibility() }
+                        { self.view_anonymous() }
+                        { self.view_roster() }
+                        { if let Some(error) = &self.state.error {
+                            html!(<ErrorBlock error=error.clone() onretry=self.link.callback(|_| Msg::Submit) />)
+                        } else {
+                            html!()
+                        } }
                         <div class="field is-grouped is-grouped-right">
                             <p class="control">
                                 <a class={button_class} onclick=self.link.callback(|_| Msg::Submit)
@@ -155,6 +427,407 @@ impl Component for CreatePoll {
 }
 
 impl CreatePoll {
+    /// Validation problems the server reported for `field`.
+    fn problems(&self, field: &str) -> Option<&Vec<String>> {
+        match &self.state.error {
+            Some(api::ApiError::Validation { fields, .. }) => fields.get(field),
+            _ => None,
+        }
+    }
+
+    fn input_class(&self, field: &str) -> &'static str {
+        if self.problems(field).is_some() {
+            "input is-danger"
+        } else {
+            "input"
+        }
+    }
+
+    fn textarea_class(&self, field: &str) -> &'static str {
+        if self.problems(field).is_some() {
+            "textarea is-danger"
+        } else {
+            "textarea"
+        }
+    }
+
+    fn field_errors(&self, field: &str) -> Html {
+        match self.problems(field) {
+            Some(problems) => html!(<p class="help is-danger">{problems.join(", ")}</p>),
+            None => html!(),
+        }
+    }
+
+    fn valid_choices(&self) -> usize {
+        self.state.choices.iter().filter(|s| !s.is_empty()).count()
+    }
+
+    /// The named categories, and each choice's category indexed into them.
+    fn categories(&self) -> (Vec<api::Category>, Vec<Option<usize>>) {
+        let named: Vec<usize> = (0..self.state.categories.len())
+            .filter(|i| !self.state.categories[*i].name.trim().is_empty())
+            .collect();
+        let categories = named
+            .iter()
+            .map(|i| api::Category {
+                name: self.state.categories[*i].name.trim().to_owned(),
+                dots: self.state.categories[*i]
+                    .dots
+                    .filter(|_| self.state.voting_method.has_budget()),
+            })
+            .collect();
+        let choice_categories = self
+            .state
+            .choice_categories
+            .iter()
+            .map(|category| category.and_then(|c| named.iter().position(|i| *i == c)))
+            .collect();
+        (categories, choice_categories)
+    }
+
+    fn budget_value(&self) -> i32 {
+        match self.state.dot_budget {
+            api::DotBudget::Fixed { dots } => dots,
+            api::DotBudget::Scaled { choices_per_dot } => choices_per_dot,
+        }
+    }
+
+    fn view_voting_method(&self) -> Html {
+        let help = VOTING_METHODS
+            .iter()
+            .find(|(method, _, _)| *method == self.state.voting_method)
+            .map(|(_, _, help)| *help)
+            .unwrap_or_default();
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{"Method"}</label>
+                </div>
+                <div class="field-body">
+                    <div class="field">
+                        <p class="control">
+                            <span class="select">
+                                <select onchange=self.link.callback(|e: ChangeData| match e {
+                                    ChangeData::Select(select) => Msg::UpdateVotingMethod(select.selected_index().max(0) as usize),
+                                    _ => Msg::UpdateVotingMethod(0),
+                                })>
+                                    { for VOTING_METHODS.iter().map(|(method, label, _)| html!(
+                                        <option selected={*method == self.state.voting_method}>{label}</option>
+                                    )) }
+                                </select>
+                            </span>
+                        </p>
+                        <p class="help">{help}</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_budget(&self) -> Html {
+        let scaled = matches!(self.state.dot_budget, api::DotBudget::Scaled { .. });
+        let dots = self.state.dot_budget.dots(self.valid_choices());
+        let (label, one, many) = if self.state.voting_method == api::VotingMethod::Quadratic {
+            ("Credits", "credit", "credits")
+        } else {
+            ("Dots", "dot", "dots")
+        };
+        let help = if scaled {
+            format!(
+                "Voters get one {} for every {} choices, currently {} {}.",
+                one,
+                self.budget_value(),
+                dots,
+                if dots == 1 { one } else { many }
+            )
+        } else {
+            format!(
+                "Every voter gets {} {}.",
+                dots,
+                if dots == 1 { one } else { many }
+            )
+        };
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{label}</label>
+                </div>
+                <div class="field-body">
+                    <div class="field">
+                        <div class="field has-addons mb-0">
+                            <p class="control">
+                                <input class="input" type="number" min="1" max=api::MAX_DOTS.to_string() value=self.budget_value()
+                                    oninput=self.link.callback(|e: InputData| Msg::UpdateBudgetValue(e.value)) />
+                            </p>
+                            <p class="control">
+                                <span class="select">
+                                    <select onchange=self.link.callback(|e: ChangeData| match e {
+                                        ChangeData::Select(select) => Msg::UpdateBudgetMode(select.value()),
+                                        _ => Msg::UpdateBudgetMode("fixed".into()),
+                                    })>
+                                        <option value="fixed" selected={!scaled}>{format!("{} per voter", many)}</option>
+                                        <option value="scaled" selected={scaled}>{format!("choices per {}", one)}</option>
+                                    </select>
+                                </span>
+                            </p>
+                        </div>
+                        <p class="help">{help}</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_rules(&self) -> Html {
+        let rules = &self.state.rules;
+        let optional = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or_default();
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{"Rules"}</label>
+                </div>
+                <div class="field-body">
+                    <div class="field">
+                        <p class="control">
+                            <input class="input" type="number" min="1" max=api::MAX_DOTS.to_string() placeholder="No limit"
+                                disabled={rules.no_stacking} value=optional(rules.max_per_choice)
+                                oninput=self.link.callback(|e: InputData| Msg::UpdateMaxPerChoice(e.value)) />
+                        </p>
+                        <p class="help">{"Maximum dots per choice"}</p>
+                    </div>
+                    <div class="field">
+                        <p class="control">
+                            <input class="input" type="number" min="1" placeholder="Any"
+                                value=optional(rules.min_choices)
+                                oninput=self.link.callback(|e: InputData| Msg::UpdateMinChoices(e.value)) />
+                        </p>
+                        <p class="help">{"Minimum choices to spread dots over"}</p>
+                    </div>
+                    <div class="field">
+                        <p class="control">
+                            <label class="checkbox pt-2">
+                                <input type="checkbox" class="mr-2" checked={rules.no_stacking}
+                                    onclick=self.link.callback(|_| Msg::ToggleNoStacking) />
+                                {"No stacking"}
+                            </label>
+                        </p>
+                        <p class="help">{"One dot per choice"}</p>
+                    </div>
+                    <div class="field">
+                        <p class="control">
+                            <input class="input" type="number" min="0" max=api::MAX_DOTS.to_string() placeholder="None"
+                                value=optional(rules.negative_dots)
+                                oninput=self.link.callback(|e: InputData| Msg::UpdateNegativeDots(e.value)) />
+                        </p>
+                        <p class="help">{"Dots voters may place against choices"}</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_funding(&self) -> Html {
+        let value = self
+            .state
+            .funding
+            .map(|funding| funding.to_string())
+            .unwrap_or_default();
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{"Funding"}</label>
+                </div>
+                <div class="field-body">
+                    <div class="field">
+                        <p class="control">
+                            <input class=self.input_class("funding") type="number" min="1" placeholder="None"
+                                value=value
+                                oninput=self.link.callback(|e: InputData| Msg::UpdateFunding(e.value)) />
+                        </p>
+                        { self.field_errors("funding") }
+                        { self.field_errors("costs") }
+                        <p class="help">{"For participatory budgeting: give choices a cost, and the best supported ones are funded out of this budget."}</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_brainstorm(&self) -> Html {
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label"></div>
+                <div class="field-body">
+                    <div class="field">
+                        <p class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" class="mr-2" checked={self.state.brainstorm}
+                                    onclick=self.link.callback(|_| Msg::ToggleBrainstorm) />
+                                {"Collect choices from participants first"}
+                            </label>
+                        </p>
+                        <p class="help">{"Anyone with the link can add choices until you start the vote, and results stay hidden until you reveal them"}</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_categories(&self) -> Html {
+        let budgets = self.state.voting_method.has_budget();
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{"Categories"}</label>
+                </div>
+                <div class="field-body">
+                    <div class="field">
+                        { for self.state.categories.iter().enumerate().map(|(i, category)| html!(
+                            <div class="field has-addons mb-2">
+                                <p class="control is-expanded">
+                                    <input class="input" type="text" value=&category.name
+                                        placeholder={if i == 0 { "Went well" } else { "" }}
+                                        oninput=self.link.callback(move |e: InputData| Msg::UpdateCategory(i, e.value)) />
+                                </p>
+                                { if budgets {
+                                    html!(
+                                        <p class="control">
+                                            <input class="input" type="number" min="1" max=api::MAX_DOTS.to_string() placeholder="Shared dots" style="width:9em;"
+                                                value=category.dots.map(|dots| dots.to_string()).unwrap_or_default()
+                                                oninput=self.link.callback(move |e: InputData| Msg::UpdateCategoryDots(i, e.value)) />
+                                        </p>
+                                    )
+                                } else {
+                                    html!()
+                                } }
+                            </div>
+                        )) }
+                        { self.field_errors("categories") }
+                        <p class="help">{"Optional columns to group choices in, like a retro board. Give a category its own dots to budget it separately, with its own winners."}</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_results_visibility(&self) -> Html {
+        let help = RESULTS_VISIBILITIES
+            .iter()
+            .find(|(visibility, _, _)| *visibility == self.state.results_visibility)
+            .map(|(_, _, help)| *help)
+            .unwrap_or_default();
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{"Results"}</label>
+                </div>
+                <div class="field-body">
+                    <div class="field">
+                        <p class="control">
+                            <span class="select">
+                                <select onchange=self.link.callback(|e: ChangeData| match e {
+                                    ChangeData::Select(select) => Msg::UpdateResultsVisibility(select.selected_index().max(0) as usize),
+                                    _ => Msg::UpdateResultsVisibility(0),
+                                })>
+                                    { for RESULTS_VISIBILITIES.iter().map(|(visibility, label, _)| html!(
+                                        <option selected={*visibility == self.state.results_visibility}>{label}</option>
+                                    )) }
+                                </select>
+                            </span>
+                        </p>
+                        <p class="help">{help}</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_anonymous(&self) -> Html {
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label"></div>
+                <div class="field-body">
+                    <div class="field">
+                        <p class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" class="mr-2" checked={self.state.anonymous}
+                                    disabled=self.has_roster()
+                                    onclick=self.link.callback(|_| Msg::ToggleAnonymous) />
+                                {"Anonymous voting"}
+                            </label>
+                        </p>
+                        <p class="help">{ if self.has_roster() {
+                            "Polls with a roster can't be anonymous, since each ballot comes from someone's invite"
+                        } else {
+                            "Voters don't give their names, and the results only show totals"
+                        } }</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn has_roster(&self) -> bool {
+        self.state
+            .roster
+            .lines()
+            .any(|name| !name.trim().is_empty())
+    }
+
+    fn view_roster(&self) -> Html {
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{"Roster"}</label>
+                </div>
+                <div class="field-body">
+                    <div class="field">
+                        <p class="control">
+                            <textarea class=self.textarea_class("roster") rows="3" placeholder="Anyone with the link"
+                                value=&self.state.roster disabled=self.state.anonymous
+                                oninput=self.link.callback(|e: InputData| Msg::UpdateRoster(e.value)) />
+                        </p>
+                        { self.field_errors("roster") }
+                        <p class="help">{ if self.state.anonymous {
+                            "Anonymous polls can't have a roster, since each invite would show whose ballot is whose"
+                        } else {
+                            "One name per line. Each person gets their own link to vote with, and you can see who hasn't voted yet."
+                        } }</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_schedule(&self) -> Html {
+        let value = |time: Option<api::Timestamp>| time.map(time::input_value).unwrap_or_default();
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{"Schedule"}</label>
+                </div>
+                <div class="field-body">
+                    <div class="field">
+                        <p class="control">
+                            <input class="input" type="datetime-local" value=value(self.state.opens_at)
+                                oninput=self.link.callback(|e: InputData| Msg::UpdateOpensAt(e.value)) />
+                        </p>
+                        <p class="help">{"Opens for votes, leave blank to open straight away"}</p>
+                    </div>
+                    <div class="field">
+                        <p class="control">
+                            <input class=self.input_class("closes_at") type="datetime-local"
+                                value=value(self.state.closes_at)
+                                oninput=self.link.callback(|e: InputData| Msg::UpdateClosesAt(e.value)) />
+                        </p>
+                        { self.field_errors("closes_at") }
+                        <p class="help">{"Closes, leave blank to keep it open until you close it"}</p>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
     fn view_answer(&self, i: usize) -> Html {
         let placeholder = if i < ANSWER_SUGGESTIONS.len() {
             ANSWER_SUGGESTIONS[i]
@@ -172,9 +845,55 @@ impl CreatePoll {
                             <input class="input" type="text" placeholder={placeholder} value=&self.state.choices[i]
                                 oninput=self.link.callback(move |e: InputData| Msg::UpdateChoice(i, e.value)) />
                         </p>
+                        { if self.state.funding.is_some() {
+                            let cost = self.state.costs[i].map(|cost| cost.to_string()).unwrap_or_default();
+                            html!(
+                                <p class="control">
+                                    <input class="input" type="number" min="0" placeholder="Cost" value=cost
+                                        oninput=self.link.callback(move |e: InputData| Msg::UpdateCost(i, e.value)) />
+                                </p>
+                            )
+                        } else {
+                            html!()
+                        } }
+                        { self.view_choice_category(i) }
                     </div>
                 </div>
             </div>
         }
     }
+
+    /// Picks the category for choice `i`, once there are categories to pick from.
+    fn view_choice_category(&self, i: usize) -> Html {
+        let named: Vec<(usize, &api::Category)> = self
+            .state
+            .categories
+            .iter()
+            .enumerate()
+            .filter(|(_, category)| !category.name.trim().is_empty())
+            .collect();
+        if named.is_empty() {
+            return html!();
+        }
+        let selected = self.state.choice_categories[i];
+        let indexes: Vec<usize> = named.iter().map(|(index, _)| *index).collect();
+        html!(
+            <p class="control">
+                <span class="select">
+                    <select onchange=self.link.callback(move |e: ChangeData| match e {
+                        ChangeData::Select(select) => Msg::UpdateChoiceCategory(
+                            i,
+                            usize::try_from(select.selected_index() - 1).ok().and_then(|c| indexes.get(c).cloned()),
+                        ),
+                        _ => Msg::UpdateChoiceCategory(i, None),
+                    })>
+                        <option selected={selected.is_none()}>{"No category"}</option>
+                        { for named.iter().map(|(index, category)| html!(
+                            <option selected={selected == Some(*index)}>{category.name.trim()}</option>
+                        )) }
+                    </select>
+                </span>
+            </p>
+        )
+    }
 }
@@ -0,0 +1,181 @@
+//! Results as files people can take elsewhere, like retro notes and spreadsheets.
+
+use std::borrow::Cow;
+
+use super::results::{standings, ResultsSort};
+use crate::api;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// One row per vote, as stored. What the value means depends on the voting method.
+    VotesCsv,
+    /// One row per choice with its total and rank.
+    TallyCsv,
+    /// The results as the API gives them. On anonymous polls the voters are random ballot labels.
+    Json,
+    Markdown,
+}
+
+pub const FORMATS: [(ExportFormat, &str); 4] = [
+    (ExportFormat::VotesCsv, "Votes CSV"),
+    (ExportFormat::TallyCsv, "Tally CSV"),
+    (ExportFormat::Json, "JSON"),
+    (ExportFormat::Markdown, "Markdown"),
+];
+
+impl ExportFormat {
+    /// Whether the format makes sense for the poll. Anonymous polls have no voters to list
+    /// votes by, only random labels, so the per-vote export is left out.
+    pub fn available(self, poll: &api::PollMetadata) -> bool {
+        !(poll.anonymous && self == ExportFormat::VotesCsv)
+    }
+
+    /// Appended to the poll's name to make the downloaded file's name.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            ExportFormat::VotesCsv => "-votes.csv",
+            ExportFormat::TallyCsv => "-tally.csv",
+            ExportFormat::Json => ".json",
+            ExportFormat::Markdown => ".md",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            ExportFormat::VotesCsv | ExportFormat::TallyCsv => "text/csv",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Markdown => "text/markdown",
+        }
+    }
+
+    pub fn render(self, results: &api::PollResults) -> String {
+        match self {
+            ExportFormat::VotesCsv => votes_csv(results),
+            ExportFormat::TallyCsv => tally_csv(results),
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(results).expect("poll results are always serializable")
+            }
+            ExportFormat::Markdown => markdown(results),
+        }
+    }
+}
+
+/// Quotes a CSV field if it needs it, per RFC 4180. Text that a spreadsheet would read as a
+/// formula gets a leading `'` so opening the export can't run anything a voter typed.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        value
+    }
+}
+
+fn votes_csv(results: &api::PollResults) -> String {
+    let mut csv = String::from("voter,choice,value,created_at\r\n");
+    for vote in &results.votes {
+        let choice = results
+            .choices
+            .iter()
+            .find(|choice| choice.id == vote.choice_id)
+            .map(|choice| choice.details.as_str())
+            .unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{},{},{}\r\n",
+            csv_field(&vote.voter),
+            csv_field(choice),
+            vote.dots,
+            vote.created_at
+        ));
+    }
+    csv
+}
+
+fn tally_csv(results: &api::PollResults) -> String {
+    let tally = api::tally::tally(results);
+    let standings = standings(results, &tally, ResultsSort::Rank);
+    let dots = standings.iter().map(|standing| standing.positive).sum();
+    let mut csv = String::from("rank,tied,choice,total,percent\r\n");
+    for standing in &standings {
+        csv.push_str(&format!(
+            "{},{},{},{},{:.1}\r\n",
+            standing.rank,
+            standing.tied,
+            csv_field(&standing.choice.details),
+            standing.total,
+            standing.share(dots)
+        ));
+    }
+    csv
+}
+
+/// Escapes the characters that would break out of a Markdown table cell. Line breaks of any
+/// kind end the row, so they become spaces.
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", " ")
+        .replace(['\r', '\n'], " ")
+}
+
+fn markdown(results: &api::PollResults) -> String {
+    let tally = api::tally::tally(results);
+    let standings = standings(results, &tally, ResultsSort::Rank);
+    let dots = standings.iter().map(|standing| standing.positive).sum();
+    let mut markdown = format!(
+        "## {}\n\n| Rank | Choice | Total | % |\n| ---: | --- | ---: | ---: |\n",
+        markdown_cell(&results.poll.title)
+    );
+    for standing in &standings {
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {:.1}% |\n",
+            standing.place(),
+            markdown_cell(&standing.choice.details),
+            standing.total,
+            standing.share(dots)
+        ));
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_when_they_need_it() {
+        assert_eq!(csv_field("Pizza"), "Pizza");
+        assert_eq!(csv_field("Pizza, large"), "\"Pizza, large\"");
+        assert_eq!(csv_field("The \"good\" one"), "\"The \"\"good\"\" one\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("two\rlines"), "\"two\rlines\"");
+    }
+
+    #[test]
+    fn csv_formulas_are_defused() {
+        assert_eq!(csv_field("=1+1"), "'=1+1");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("\tTab"), "'\tTab");
+        assert_eq!(csv_field("=A1,B1"), "\"'=A1,B1\"");
+        assert_eq!(csv_field("\r=A1"), "\"'\r=A1\"");
+        assert_eq!(csv_field("1+1="), "1+1=");
+    }
+
+    #[test]
+    fn markdown_cells_stay_in_their_cell() {
+        assert_eq!(markdown_cell("Pizza"), "Pizza");
+        assert_eq!(markdown_cell("This | that"), "This \\| that");
+        assert_eq!(markdown_cell("back\\slash"), "back\\\\slash");
+        assert_eq!(markdown_cell("\\|"), "\\\\\\|");
+        assert_eq!(markdown_cell("one\ntwo"), "one two");
+        assert_eq!(markdown_cell("one\r\ntwo"), "one two");
+        assert_eq!(markdown_cell("one\rtwo"), "one two");
+    }
+}
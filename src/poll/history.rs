@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::saved::{self, SavedBallot, SavedPoll};
+use crate::api;
+use crate::component::{Panel, PanelBlock, PanelHeading};
+use crate::download;
+use crate::time;
+use yew::prelude::*;
+use yew::services::reader::{File, FileData, ReaderService, ReaderTask};
+use yew::services::storage::{Area, StorageService};
+use yew::services::ConsoleService;
+use yew_router::prelude::*;
+
+/// A poll from the history, with what this browser knows about it.
+struct Entry {
+    saved: SavedPoll,
+    ballot: Option<SavedBallot>,
+    admin_token: Option<api::AdminToken>,
+    // Filled in as each poll is fetched
+    poll: Option<Result<api::Poll, api::ApiError>>,
+}
+
+pub enum Msg {
+    Fetched(api::PollUuid, Result<api::Poll, api::ApiError>),
+    Forget(api::PollUuid),
+    Export,
+    Import(Option<File>),
+    Imported(FileData),
+}
+
+/// Every poll this browser created or voted in, from `saved`'s history.
+pub struct MyPolls {
+    link: ComponentLink<Self>,
+    storage: StorageService,
+    entries: Vec<Entry>,
+    // What happened to the last import, and whether it went wrong
+    notice: Option<(String, bool)>,
+    backend: Rc<dyn api::PollBackend>,
+    tasks: Vec<api::ApiTask>,
+    _reader: Option<ReaderTask>,
+}
+
+impl Component for MyPolls {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut polls = Self {
+            link,
+            storage: StorageService::new(Area::Local).expect("browser storage disabled"),
+            entries: Vec::new(),
+            notice: None,
+            backend: api::backend(),
+            tasks: Vec::new(),
+            _reader: None,
+        };
+        polls.load();
+        polls
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Fetched(poll_id, poll) => {
+                match self
+                    .entries
+                    .iter_mut()
+                    .find(|entry| entry.saved.poll == poll_id)
+                {
+                    Some(entry) => {
+                        entry.poll = Some(poll);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Msg::Forget(poll_id) => {
+                saved::forget_poll(&mut self.storage, poll_id.as_str());
+                self.entries.retain(|entry| entry.saved.poll != poll_id);
+                true
+            }
+            Msg::Export => {
+                let history = saved::export_history(&self.storage);
+                let contents = serde_json::to_string_pretty(&history)
+                    .expect("history always serializes to JSON");
+                if let Err(error) =
+                    download::download("dotdotvote-history.json", "application/json", &contents)
+                {
+                    ConsoleService::error(&format!("Couldn't export history: {:?}", error));
+                }
+                false
+            }
+            Msg::Import(None) => false,
+            Msg::Import(Some(file)) => {
+                let callback = self.link.callback(Msg::Imported);
+                match ReaderService::new().read_file(file, callback) {
+                    Ok(task) => self._reader = Some(task),
+                    Err(error) => {
+                        self.notice = Some((format!("Couldn't read that file: {}", error), true))
+                    }
+                }
+                true
+            }
+            Msg::Imported(file) => {
+                self._reader = None;
+                self.notice = Some(match serde_json::from_slice(&file.content) {
+                    Ok(history) => {
+                        let added = saved::import_history(&mut self.storage, history);
+                        self.load();
+                        (
+                            format!(
+                                "Imported {} new {} from {}.",
+                                added,
+                                if added == 1 { "poll" } else { "polls" },
+                                file.name
+                            ),
+                            false,
+                        )
+                    }
+                    Err(_) => (
+                        format!("{} isn't a dotdotvote history export.", file.name),
+                        true,
+                    ),
+                });
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html!(
+            <Panel>
+                <PanelHeading>
+                    <div class="level">
+                        <div class="level-left">
+                            <div class="level-item">
+                                {"My polls"}
+                            </div>
+                        </div>
+                        <div class="level-right">
+                            <div class="level-item">
+                                <RouterButton<crate::MountedRoute> route={crate::MountedRoute(crate::AppRoute::Index)} classes="button is-primary is-small">
+                                    <span class="icon"><i class="fas fa-plus"></i></span>
+                                    <span>{"New poll"}</span>
+                                </RouterButton<crate::MountedRoute>>
+                            </div>
+                        </div>
+                    </div>
+                </PanelHeading>
+                { if self.entries.is_empty() {
+                    html!(
+                        <PanelBlock>
+                            <p class="has-text-grey">{"Polls you create or vote in from this browser will show up here."}</p>
+                        </PanelBlock>
+                    )
+                } else {
+                    html!({ for self.entries.iter().map(|entry| self.view_entry(entry)) })
+                } }
+                { match &self.notice {
+                    Some((notice, failed)) => html!(
+                        <PanelBlock class=if *failed { "notification is-danger is-light mb-0" } else { "notification is-success is-light mb-0" }>
+                            <p>{notice}</p>
+                        </PanelBlock>
+                    ),
+                    None => html!(),
+                } }
+                <PanelBlock>
+                    <div class="level" style="width:100%;">
+                        <div class="level-left">
+                            <p class="level-item help">
+                                {"Exports include your ballots and admin links, so keep them private."}
+                            </p>
+                        </div>
+                        <div class="level-right">
+                            <div class="level-item buttons">
+                                <button class="button is-light" disabled=self.entries.is_empty()
+                                    onclick=self.link.callback(|_| Msg::Export)>
+                                    <span class="icon"><i class="fas fa-download"></i></span>
+                                    <span>{"Export"}</span>
+                                </button>
+                                <div class="file">
+                                    <label class="file-label">
+                                        <input class="file-input" type="file" accept="application/json,.json"
+                                            onchange=self.link.callback(|e: ChangeData| match e {
+                                                ChangeData::Files(files) => Msg::Import(files.get(0)),
+                                                _ => Msg::Import(None),
+                                            }) />
+                                        <span class="file-cta">
+                                            <span class="file-icon"><i class="fas fa-upload"></i></span>
+                                            <span class="file-label">{"Import"}</span>
+                                        </span>
+                                    </label>
+                                </div>
+                            </div>
+                        </div>
+                    </div>
+                </PanelBlock>
+            </Panel>
+        )
+    }
+}
+
+impl MyPolls {
+    /// Reads the history back out of storage and fetches every poll in it.
+    fn load(&mut self) {
+        self.entries = saved::restore_history(&self.storage)
+            .into_iter()
+            .map(|saved| Entry {
+                ballot: saved::restore_ballot(&self.storage, saved.poll.as_str()),
+                admin_token: saved::restore_admin_token(&self.storage, saved.poll.as_str()),
+                saved,
+                poll: None,
+            })
+            .collect();
+        self.tasks = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let backend = self.backend.clone();
+                let poll_id = entry.saved.poll.clone();
+                api::spawn(&self.link, async move {
+                    let poll = backend.get_poll(&poll_id).await;
+                    Msg::Fetched(poll_id, poll)
+                })
+            })
+            .collect();
+    }
+
+    fn view_entry(&self, entry: &Entry) -> Html {
+        let poll_id = entry.saved.poll.as_str().to_owned();
+        let poll = match &entry.poll {
+            Some(Ok(poll)) => Some(poll),
+            _ => None,
+        };
+        let title = poll
+            .map(|poll| poll.poll.title.as_str())
+            .unwrap_or(&entry.saved.title);
+        let status = match &entry.poll {
+            None => html!(<span class="tag is-light">{"Loading"}</span>),
+            Some(Ok(poll)) => match poll.poll.status(time::now()) {
+                api::PollStatus::Scheduled => {
+                    html!(<span class="tag is-info is-light">{"Scheduled"}</span>)
+                }
+                api::PollStatus::Open => {
+                    html!(<span class="tag is-success is-light">{"Open"}</span>)
+                }
+                api::PollStatus::Closed => html!(<span class="tag is-dark">{"Closed"}</span>),
+            },
+            Some(Err(api::ApiError::NotFound)) => {
+                html!(<span class="tag is-danger is-light">{"Deleted"}</span>)
+            }
+            Some(Err(_)) => html!(<span class="tag is-warning is-light">{"Unavailable"}</span>),
+        };
+        let my_vote = match (&entry.ballot, poll) {
+            (Some(ballot), Some(poll)) => describe_ballot(ballot, poll),
+            (Some(_), None) => "Voted".into(),
+            (None, _) => "Not voted".into(),
+        };
+        let forget = entry.saved.poll.clone();
+        html!(
+            <PanelBlock>
+                <div class="level" style="width:100%;">
+                    <div class="level-left">
+                        <div class="level-item">
+                            <div>
+                                <p>
+                                    <strong>{title}</strong>{" "}
+                                    {status}{" "}
+                                    { if entry.admin_token.is_some() {
+                                        html!(<span class="tag is-primary is-light">{"Created"}</span>)
+                                    } else {
+                                        html!()
+                                    } }
+                                </p>
+                                <p class="is-size-7 has-text-grey">{my_vote}</p>
+                            </div>
+                        </div>
+                    </div>
+                    <div class="level-right">
+                        <div class="level-item buttons are-small">
+                            { self.route(crate::AppRoute::Poll(poll_id.clone()), "Vote", "fas fa-vote-yea") }
+                            { self.route(crate::AppRoute::PollResults(poll_id.clone()), "Results", "fas fa-chart-bar") }
+                            { match &entry.admin_token {
+                                Some(token) => self.route(crate::AppRoute::PollAdmin(poll_id, token.to_string()), "Manage", "fas fa-cog"),
+                                None => html!(),
+                            } }
+                            <button class="button is-light" title="Forget this poll"
+                                onclick=self.link.callback(move |_| Msg::Forget(forget.clone()))>
+                                <span class="icon"><i class="fas fa-times"></i></span>
+                            </button>
+                        </div>
+                    </div>
+                </div>
+            </PanelBlock>
+        )
+    }
+
+    fn route(&self, route: crate::AppRoute, label: &str, icon: &str) -> Html {
+        html!(
+            <RouterButton<crate::MountedRoute> route={crate::MountedRoute(route)} classes="button is-light">
+                <span class="icon"><i class=icon></i></span>
+                <span>{label}</span>
+            </RouterButton<crate::MountedRoute>>
+        )
+    }
+}
+
+/// The ballot cast from here, in words.
+fn describe_ballot(ballot: &SavedBallot, poll: &api::Poll) -> String {
+    let details: HashMap<api::ChoiceId, &str> = poll
+        .choices
+        .iter()
+        .map(|choice| (choice.id, choice.details.as_str()))
+        .collect();
+    let mut picked: Vec<(&str, i32)> = ballot
+        .choices
+        .iter()
+        .filter(|(_, value)| **value != 0)
+        .filter_map(|(id, value)| Some((*details.get(id)?, *value)))
+        .collect();
+    let method = poll.poll.voting_method;
+    if method.is_ranked() {
+        picked.sort_by_key(|(_, rank)| *rank);
+    } else {
+        picked.sort_by_key(|(_, value)| -value);
+    }
+    let picked: Vec<String> = picked
+        .iter()
+        .map(|(details, value)| match method {
+            api::VotingMethod::Approval => details.to_string(),
+            _ if method.is_ranked() => format!("{}. {}", value, details),
+            _ => format!("{} ({})", details, value),
+        })
+        .collect();
+    let voter = if ballot.voter.is_empty() {
+        "You".to_owned()
+    } else {
+        ballot.voter.clone()
+    };
+    if picked.is_empty() {
+        format!("{} voted", voter)
+    } else {
+        format!("{} voted: {}", voter, picked.join(", "))
+    }
+}
@@ -1,7 +1,13 @@
+mod admin;
 mod create;
+mod export;
+mod history;
 mod results;
+mod saved;
 mod show;
 
+pub use admin::PollAdmin;
 pub use create::CreatePoll;
-pub use results::PollResults;
+pub use history::MyPolls;
+pub use results::{PollResults, ResultsSort};
 pub use show::ShowPoll;
@@ -1,36 +1,248 @@
+use super::export::{ExportFormat, FORMATS};
+use super::saved;
 use crate::api;
-use crate::component::{Panel, PanelBlock, PanelHeading};
+use crate::component::{
+    BarChart, ChartRow, ErrorPanel, Panel, PanelBlock, PanelHeading, PieChart, Segment,
+};
+use crate::download;
+use futures::StreamExt;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
-use yew::format::Json;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::Duration;
 use yew::prelude::*;
-use yew::services::fetch::FetchTask;
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::services::storage::{Area, StorageService};
+use yew::services::ConsoleService;
+use yew_router::agent::{RouteAgentDispatcher, RouteRequest};
+use yew_router::prelude::*;
+use yew_router::route::Route;
 
 const COLOURS: [&str; 12] = [
     "#8ecbb7", "#e4aee0", "#88ddad", "#efa6a6", "#6adcdc", "#e8ba85", "#77cdef", "#d7e599",
     "#acb9ec", "#a0c583", "#c6f0ce", "#d1c99a",
 ];
 
+/// Past this many, a choice's dots are counted rather than drawn one by one.
+const MAX_DRAWN_DOTS: i64 = 40;
+
+/// How often to refetch results when the backend can't push new votes.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How choices are ordered on the results page. Part of the URL, so shared links keep it.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ResultsSort {
+    #[default]
+    Rank,
+    Original,
+    Alphabetical,
+}
+
+const SORTS: [(ResultsSort, &str); 3] = [
+    (ResultsSort::Rank, "Rank"),
+    (ResultsSort::Original, "Original"),
+    (ResultsSort::Alphabetical, "A-Z"),
+];
+
+impl FromStr for ResultsSort {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rank" => Ok(ResultsSort::Rank),
+            "original" => Ok(ResultsSort::Original),
+            "alphabetical" => Ok(ResultsSort::Alphabetical),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for ResultsSort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ResultsSort::Rank => "rank",
+            ResultsSort::Original => "original",
+            ResultsSort::Alphabetical => "alphabetical",
+        })
+    }
+}
+
 #[derive(Properties, Clone, PartialEq)]
 pub struct Props {
     pub poll_id: String,
+    #[prop_or_default]
+    pub sort: ResultsSort,
+}
+
+/// Where a choice placed. Tied choices all get the best rank between them.
+pub(super) struct Standing<'a> {
+    pub choice: &'a api::PollChoice,
+    /// Position in the poll as created, which also picks the choice's chart colour.
+    pub index: usize,
+    pub total: i32,
+    pub credits: i32,
+    pub positive: i32,
+    pub negative: i32,
+    pub rank: usize,
+    pub tied: bool,
+}
+
+/// Every choice with its total and rank from `tally`, in `sort` order.
+pub(super) fn standings<'a>(
+    results: &'a api::PollResults,
+    tally: &api::tally::Tally,
+    sort: ResultsSort,
+) -> Vec<Standing<'a>> {
+    let mut standings: Vec<Standing> = results
+        .choices
+        .iter()
+        .enumerate()
+        .filter_map(|(index, choice)| {
+            let counted = tally.get(choice.id)?;
+            Some(Standing {
+                choice,
+                index,
+                total: counted.total,
+                credits: counted.credits,
+                positive: counted.positive,
+                negative: counted.negative,
+                rank: counted.rank,
+                tied: counted.tied,
+            })
+        })
+        .collect();
+    match sort {
+        ResultsSort::Rank => standings.sort_by_key(|standing| standing.rank),
+        ResultsSort::Original => {}
+        ResultsSort::Alphabetical => {
+            standings.sort_by_key(|standing| standing.choice.details.to_lowercase())
+        }
+    }
+    standings
+}
+
+/// What a choice's total counts under `method`.
+pub(super) fn unit(method: api::VotingMethod, total: i32) -> &'static str {
+    let (one, many) = match method {
+        api::VotingMethod::Dots | api::VotingMethod::Quadratic => ("dot", "dots"),
+        api::VotingMethod::Approval => ("approval", "approvals"),
+        api::VotingMethod::Score | api::VotingMethod::Borda => ("point", "points"),
+        api::VotingMethod::RankedChoice => ("vote", "votes"),
+    };
+    if total == 1 {
+        one
+    } else {
+        many
+    }
+}
+
+/// `text` with its first letter capitalised, for showing messages meant to follow on from
+/// something else as sentences of their own.
+pub(super) fn capitalise(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// `count` dots drawn as `icon`, or a single one with the count beside it once there are too
+/// many to draw.
+pub(super) fn draw_dots(count: i32, class: &'static str, style: &str, icon: &'static str) -> Html {
+    let dot = || html!(<span class=class style=style.to_owned()><i class=icon></i></span>);
+    if i64::from(count) > MAX_DRAWN_DOTS {
+        html!(<>{dot()}<span class="mr-1">{format!("\u{d7}{}", count)}</span></>)
+    } else {
+        (0..count).map(|_| dot()).collect()
+    }
+}
+
+impl Standing<'_> {
+    /// This choice's percentage of `dots`.
+    pub fn share(&self, dots: i32) -> f64 {
+        if dots == 0 {
+            0.0
+        } else {
+            100.0 * f64::from(self.total) / f64::from(dots)
+        }
+    }
+
+    /// The rank as shown to people, with ties marked like `=2nd`.
+    pub fn place(&self) -> String {
+        format!("{}{}", if self.tied { "=" } else { "" }, ordinal(self.rank))
+    }
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
 }
 
+/// The ways results can be shown, picked with the tabs on the results panel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum View {
+    Dots,
+    Bars,
+    Stacked,
+    Pie,
+}
+
+const VIEWS: [(View, &str); 4] = [
+    (View::Dots, "Dots"),
+    (View::Bars, "Bar"),
+    (View::Stacked, "By voter"),
+    (View::Pie, "Pie"),
+];
+
 struct State {
+    view: View,
+    funding_strategy: api::tally::FundingStrategy,
     results: Option<api::PollResults>,
+    // The poll, when its results are hidden from this browser
+    hidden: Option<api::Poll>,
     voter_colours: HashMap<String, &'static str>,
+    // Votes that arrived in the latest update, animated in when rendered
+    fresh: HashSet<api::VoteId>,
+    // Events that arrived before the first fetch finished, replayed onto its results
+    early: Vec<api::PollEvent>,
+    live: bool,
+    error: Option<api::ApiError>,
 }
 
 pub enum Msg {
     FetchSuccess(api::PollResults),
-    FetchFailed,
+    Hidden(api::Poll),
+    FetchFailed(api::ApiError),
+    Retry,
+    Event(api::PollEvent),
+    EventsEnded,
+    Refresh,
+    ShowView(View),
+    ShowFunding(api::tally::FundingStrategy),
+    Sort(ResultsSort),
+    Export(ExportFormat),
 }
 
 pub struct PollResults {
     link: ComponentLink<Self>,
     props: Props,
     state: State,
-    tasks: Vec<FetchTask>,
+    backend: Rc<dyn api::PollBackend>,
+    // Sent along to see results hidden from others, see `saved::results_token`
+    token: Option<String>,
+    router: RouteAgentDispatcher<()>,
+    // The latest fetch. Starting another drops, and so cancels, the last.
+    fetching: Option<api::ApiTask>,
+    _events: Option<api::ApiTask>,
+    _refresh: Option<IntervalTask>,
 }
 
 impl Component for PollResults {
@@ -38,48 +250,219 @@ impl Component for PollResults {
     type Properties = Props;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let task = api::get_results(&props.poll_id, &link, |response| {
-            if let (meta, Json(Ok(body))) = response.into_parts() {
-                if meta.status.is_success() {
-                    return Msg::FetchSuccess(body);
-                }
-            }
-            Msg::FetchFailed
-        });
+        let backend = api::backend();
+        let token = StorageService::new(Area::Local)
+            .ok()
+            .and_then(|storage| saved::results_token(&storage, &props.poll_id));
+        // Subscribe before fetching so no votes slip through in between
+        let events = Self::watch(&backend, &props.poll_id, token.as_deref(), &link);
+        let refresh = match events {
+            Some(_) => None,
+            None => Some(Self::refresh_every(&link)),
+        };
+        let task = Self::fetch(&backend, &props.poll_id, token.clone(), &link);
 
         Self {
             link,
             props,
             state: State {
+                view: View::Dots,
+                funding_strategy: api::tally::FundingStrategy::Greedy,
                 results: None,
+                hidden: None,
                 voter_colours: HashMap::new(),
+                fresh: HashSet::new(),
+                early: Vec::new(),
+                live: events.is_some(),
+                error: None,
             },
-            tasks: vec![task],
+            backend,
+            token,
+            router: RouteAgentDispatcher::new(),
+            fetching: Some(task),
+            _events: events,
+            _refresh: refresh,
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> bool {
         match msg {
             Msg::FetchSuccess(results) => {
-                let mut colour_index: usize = 0;
-                for vote in results.votes.iter() {
-                    match self.state.voter_colours.entry(vote.voter.clone()) {
-                        Entry::Occupied(_) => {}
-                        Entry::Vacant(entry) => {
-                            entry.insert(COLOURS[colour_index]);
-                            colour_index = (colour_index + 1) % 12;
-                        }
+                self.state.fresh = match &self.state.results {
+                    Some(previous) => results
+                        .votes
+                        .iter()
+                        .filter(|vote| !previous.votes.iter().any(|v| v.id == vote.id))
+                        .map(|vote| vote.id)
+                        .collect(),
+                    None => HashSet::new(),
+                };
+                self.assign_colours(&results.votes);
+                self.state.results = Some(results);
+                self.state.hidden = None;
+                // The fetch may have been answered before these happened. Replaying is safe
+                // either way, as votes already there are skipped.
+                for event in std::mem::take(&mut self.state.early) {
+                    self.update(Msg::Event(event));
+                }
+                true
+            }
+            Msg::Hidden(poll) => {
+                self.state.results = None;
+                self.state.hidden = Some(poll);
+                self.state.early.clear();
+                true
+            }
+            Msg::Event(event)
+                if self.state.results.is_none()
+                    && self.state.hidden.is_none()
+                    && self.state.error.is_none() =>
+            {
+                self.state.early.push(event);
+                false
+            }
+            Msg::Event(api::PollEvent::Voted { votes }) => {
+                let results = match &mut self.state.results {
+                    Some(results) => results,
+                    None => return false,
+                };
+                let votes: Vec<api::Vote> = votes
+                    .into_iter()
+                    .filter(|vote| !results.votes.iter().any(|v| v.id == vote.id))
+                    .collect();
+                let invite_only = results.poll.invite_only;
+                self.state.fresh = votes.iter().map(|vote| vote.id).collect();
+                results.votes.extend(votes.iter().cloned());
+                self.assign_colours(&votes);
+                // Only the server knows whose invite a ballot came from
+                if invite_only {
+                    self.update(Msg::Refresh);
+                }
+                true
+            }
+            Msg::Event(api::PollEvent::Withdrawn { votes }) => match &mut self.state.results {
+                Some(results) => {
+                    results.votes.retain(|vote| !votes.contains(&vote.id));
+                    if results.poll.invite_only {
+                        self.update(Msg::Refresh);
                     }
+                    true
                 }
-                self.state.results = Some(results);
+                None => false,
+            },
+            Msg::Event(api::PollEvent::Updated { poll }) => match &mut self.state.results {
+                Some(results) => {
+                    results.votes.retain(|vote| {
+                        poll.choices
+                            .iter()
+                            .any(|choice| choice.id == vote.choice_id)
+                    });
+                    results.poll = poll.poll;
+                    results.choices = poll.choices;
+                    true
+                }
+                // Closing or revealing the poll can bring hidden results out
+                None if self.state.hidden.is_some() => {
+                    self.state.hidden = Some(poll);
+                    self.update(Msg::Refresh);
+                    true
+                }
+                None => false,
+            },
+            Msg::EventsEnded => {
+                self.state.live = false;
+                self._events = None;
+                self._refresh = Some(Self::refresh_every(&self.link));
+                // Catch up on anything missed while the stream was failing
+                self.update(Msg::Refresh)
+            }
+            Msg::Refresh => {
+                let task = Self::fetch(
+                    &self.backend,
+                    &self.props.poll_id,
+                    self.token.clone(),
+                    &self.link,
+                );
+                self.fetching = Some(task);
+                false
+            }
+            Msg::ShowView(view) => {
+                self.state.view = view;
+                true
+            }
+            Msg::ShowFunding(strategy) => {
+                self.state.funding_strategy = strategy;
+                true
+            }
+            Msg::Sort(sort) => {
+                let route = crate::AppRoute::SortedPollResults(self.props.poll_id.clone(), sort);
+                self.router.send(RouteRequest::ReplaceRoute(Route::from(
+                    crate::MountedRoute(route),
+                )));
+                false
+            }
+            Msg::Export(format) => {
+                if let Some(results) = &self.state.results {
+                    let filename = download::filename(&results.poll.title, format.suffix());
+                    if let Err(error) =
+                        download::download(&filename, format.mime(), &format.render(results))
+                    {
+                        ConsoleService::error(&format!("Couldn't export results: {:?}", error));
+                    }
+                }
+                false
+            }
+            Msg::FetchFailed(error) => {
+                self.state.error = Some(error);
+                true
+            }
+            Msg::Retry => {
+                self.state.error = None;
+                let task = Self::fetch(
+                    &self.backend,
+                    &self.props.poll_id,
+                    self.token.clone(),
+                    &self.link,
+                );
+                self.fetching = Some(task);
                 true
             }
-            Msg::FetchFailed => false,
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> bool {
-        if self.props != props {
+        if self.props.poll_id != props.poll_id {
+            self.token = StorageService::new(Area::Local)
+                .ok()
+                .and_then(|storage| saved::results_token(&storage, &props.poll_id));
+            // Drop the old poll's stream before subscribing to the new one
+            self._events = None;
+            self._events = Self::watch(
+                &self.backend,
+                &props.poll_id,
+                self.token.as_deref(),
+                &self.link,
+            );
+            self._refresh = match self._events {
+                Some(_) => None,
+                None => Some(Self::refresh_every(&self.link)),
+            };
+            self.fetching = Some(Self::fetch(
+                &self.backend,
+                &props.poll_id,
+                self.token.clone(),
+                &self.link,
+            ));
+            self.state.results = None;
+            self.state.hidden = None;
+            self.state.voter_colours.clear();
+            self.state.fresh.clear();
+            self.state.early.clear();
+            self.state.live = self._events.is_some();
+            self.state.error = None;
+            self.props = props;
+            true
+        } else if self.props != props {
             self.props = props;
             true
         } else {
@@ -88,8 +471,12 @@ impl Component for PollResults {
     }
 
     fn view(&self) -> Html {
-        if let Some(results) = &self.state.results {
+        if let Some(error) = &self.state.error {
+            html!(<ErrorPanel error=error.clone() onretry=self.link.callback(|_| Msg::Retry) />)
+        } else if let Some(results) = &self.state.results {
             self.show_results(results)
+        } else if let Some(poll) = &self.state.hidden {
+            self.show_hidden(poll)
         } else {
             html!(
                 <Panel>
@@ -101,9 +488,107 @@ impl Component for PollResults {
 }
 
 impl PollResults {
+    fn watch(
+        backend: &Rc<dyn api::PollBackend>,
+        poll_id: &str,
+        token: Option<&str>,
+        link: &ComponentLink<Self>,
+    ) -> Option<api::ApiTask> {
+        let mut events = backend.watch(&api::PollUuid::from(poll_id), token)?;
+        let forward = link.clone();
+        Some(api::spawn(link, async move {
+            while let Some(event) = events.next().await {
+                forward.send_message(Msg::Event(event));
+            }
+            Msg::EventsEnded
+        }))
+    }
+
+    fn refresh_every(link: &ComponentLink<Self>) -> IntervalTask {
+        IntervalService::spawn(REFRESH_INTERVAL, link.callback(|_| Msg::Refresh))
+    }
+
+    /// Gives every voter not seen before the next colour.
+    fn assign_colours(&mut self, votes: &[api::Vote]) {
+        for vote in votes {
+            let next = COLOURS[self.state.voter_colours.len() % COLOURS.len()];
+            if let Entry::Vacant(entry) = self.state.voter_colours.entry(vote.voter.clone()) {
+                entry.insert(next);
+            }
+        }
+    }
+
+    /// Fetches the results, or the poll itself when they're hidden from this browser.
+    fn fetch(
+        backend: &Rc<dyn api::PollBackend>,
+        poll_id: &str,
+        token: Option<String>,
+        link: &ComponentLink<Self>,
+    ) -> api::ApiTask {
+        let backend = backend.clone();
+        let poll_id = api::PollUuid::from(poll_id);
+        api::spawn(link, async move {
+            match backend.get_results(&poll_id, token.as_deref()).await {
+                Ok(results) => Msg::FetchSuccess(results),
+                Err(api::ApiError::Forbidden { .. }) => match backend.get_poll(&poll_id).await {
+                    Ok(poll) => Msg::Hidden(poll),
+                    Err(error) => Msg::FetchFailed(error),
+                },
+                Err(error) => Msg::FetchFailed(error),
+            }
+        })
+    }
+
+    /// Explains why the results are hidden, in place of them.
+    fn show_hidden(&self, poll: &api::Poll) -> Html {
+        let message = format!("{}.", capitalise(poll.poll.results_hidden_reason()));
+        let can_vote = poll.poll.taking_votes(crate::time::now())
+            && poll.poll.results_visibility == api::ResultsVisibility::AfterVote;
+        html!(
+            <Panel>
+                <PanelHeading>
+                    <div class="level">
+                        <div class="level-left">
+                            <div class="level-item">
+                                {poll.poll.title.clone() + " - Results"}
+                            </div>
+                        </div>
+                    </div>
+                </PanelHeading>
+                <PanelBlock class="notification is-warning is-light mb-0">
+                    <span class="panel-icon"><i class="fas fa-lock" aria-hidden="true"></i></span>
+                    {message}
+                </PanelBlock>
+                <PanelBlock>
+                    <RouterButton<crate::MountedRoute> route={crate::MountedRoute(crate::AppRoute::Poll(self.props.poll_id.clone()))} classes="button is-primary is-fullwidth">
+                        { if can_vote { "Vote to see the results" } else { "Back to the poll" } }
+                    </RouterButton<crate::MountedRoute>>
+                </PanelBlock>
+            </Panel>
+        )
+    }
+
     fn show_results(&self, results: &api::PollResults) -> Html {
         let title = results.poll.title.clone() + " - Results";
-        let votes = self.state.voter_colours.len();
+        // Ballots, not voter names or colours, which outlive withdrawn and changed ballots
+        let votes = results
+            .votes
+            .iter()
+            .map(|vote| vote.ballot)
+            .collect::<HashSet<_>>()
+            .len();
+        let tally = api::tally::tally(results);
+        let standings = standings(results, &tally, self.props.sort);
+        let dots = standings.iter().map(|standing| standing.positive).sum();
+        let funding = api::tally::fund(results, &tally, self.state.funding_strategy);
+        let status = match results.poll.status(crate::time::now()) {
+            api::PollStatus::Closed => html!(
+                <span class="tag is-dark ml-2" title="Voting has closed">{"Final"}</span>
+            ),
+            _ => html!(
+                <span class="tag is-warning is-light ml-2" title="Voting is still open, so these could change">{"Provisional"}</span>
+            ),
+        };
         html!(
             <Panel>
                 <PanelHeading>
@@ -111,32 +596,361 @@ impl PollResults {
                         <div class="level-left">
                             <div class="level-item">
                                 {title}
+                                { status }
+                                { if self.state.live { html!(<span class="tag is-success is-light ml-2">{"Live"}</span>) } else { html!() } }
                             </div>
                         </div>
                         <div class="level-right">
                             <div class="level-item">
-                                {format!("{} Vote{} Submitted", votes, if votes > 1 { "s" } else { "" })}
+                                {format!("{} Vote{} Submitted", votes, if votes == 1 { "" } else { "s" })}
                             </div>
                         </div>
                     </div>
                 </PanelHeading>
-                { for results.choices.iter().map(|choice| self.show_choice(choice)) }
+                <p class="panel-tabs">
+                    { for VIEWS
+                        .iter()
+                        .filter(|(view, _)| !(results.poll.anonymous && *view == View::Stacked))
+                        .map(|(view, label)| self.view_tab(*view, label)) }
+                </p>
+                <PanelBlock>
+                    <span class="mr-2">{"Sort by"}</span>
+                    <div class="buttons has-addons are-small">
+                        { for SORTS.iter().map(|(sort, label)| self.view_sort(*sort, label)) }
+                    </div>
+                </PanelBlock>
+                { match self.state.view {
+                    View::Dots if !results.poll.categories.is_empty() => self.show_categories(results, &tally, &standings, funding.as_ref()),
+                    View::Dots => standings.iter().map(|standing| self.show_choice(standing, dots, funding.as_ref())).collect::<Html>(),
+                    View::Bars => html!(<PanelBlock style="display:block;"><BarChart rows=self.chart_rows(&standings) /></PanelBlock>),
+                    View::Stacked => html!(<PanelBlock style="display:block;"><BarChart rows=self.chart_rows(&standings) stacked=true /></PanelBlock>),
+                    View::Pie => html!(<PanelBlock style="display:block;"><PieChart rows=self.chart_rows(&standings) /></PanelBlock>),
+                } }
+                { self.show_rounds(results, &tally) }
+                { self.show_funding(results, funding.as_ref()) }
+                { self.show_participation(results) }
+                <PanelBlock>
+                    <span class="mr-2">{"Export"}</span>
+                    <div class="buttons are-small">
+                        { for FORMATS.iter().filter(|(format, _)| format.available(&results.poll)).map(|(format, label)| {
+                            let format = *format;
+                            html!(<button class="button" onclick=self.link.callback(move |_| Msg::Export(format))>
+                                <span class="icon"><i class="fas fa-download"></i></span>
+                                <span>{label}</span>
+                            </button>)
+                        }) }
+                    </div>
+                </PanelBlock>
             </Panel>
         )
     }
 
-    fn show_choice(&self, choice: &api::PollChoice) -> Html {
-        let votes: Vec<(&String, &'static str)> = self
-            .state
-            .results
-            .as_ref()
-            .unwrap()
+    fn view_tab(&self, view: View, label: &str) -> Html {
+        let class = if self.state.view == view {
+            "is-active"
+        } else {
+            ""
+        };
+        html!(<a class=class onclick=self.link.callback(move |_| Msg::ShowView(view))>{label}</a>)
+    }
+
+    fn view_sort(&self, sort: ResultsSort, label: &str) -> Html {
+        let class = if self.props.sort == sort {
+            "button is-primary is-selected"
+        } else {
+            "button"
+        };
+        html!(<button class=class onclick=self.link.callback(move |_| Msg::Sort(sort))>{label}</button>)
+    }
+
+    /// Tallies for the charts, one row per choice split up by voter. Anonymous polls only get
+    /// the dots for and against each choice, so nothing is broken down by ballot.
+    fn chart_rows(&self, standings: &[Standing]) -> Vec<ChartRow> {
+        let results = self.state.results.as_ref().unwrap();
+        standings
+            .iter()
+            .map(|standing| {
+                let colour = COLOURS[standing.index % COLOURS.len()];
+                let segments = if results.poll.anonymous {
+                    vec![
+                        Segment {
+                            label: "For".into(),
+                            colour,
+                            value: standing.positive,
+                        },
+                        Segment {
+                            label: "Against".into(),
+                            colour,
+                            value: -standing.negative,
+                        },
+                    ]
+                } else {
+                    results
+                        .votes
+                        .iter()
+                        .filter(|vote| vote.choice_id == standing.choice.id)
+                        .map(|vote| Segment {
+                            label: vote.voter.clone(),
+                            colour: self.state.voter_colours[&vote.voter],
+                            value: api::tally::points(
+                                results.poll.voting_method,
+                                vote.dots,
+                                results.choices.len(),
+                            ),
+                        })
+                        .collect()
+                };
+                ChartRow {
+                    label: standing.choice.details.clone(),
+                    colour,
+                    segments,
+                }
+            })
+            .collect()
+    }
+
+    /// The instant runoff count, round by round, for ranked choice polls.
+    fn show_rounds(&self, results: &api::PollResults, tally: &api::tally::Tally) -> Html {
+        if tally.rounds.is_empty() {
+            return html!();
+        }
+        let details = |id: &api::ChoiceId| {
+            results
+                .choices
+                .iter()
+                .find(|choice| choice.id == *id)
+                .map(|choice| choice.details.clone())
+                .unwrap_or_default()
+        };
+        html!(
+            <PanelBlock style="display:block;">
+                <p class="has-text-weight-semibold mb-2">{"Rounds"}</p>
+                <ol class="ml-5">
+                    { for tally.rounds.iter().map(|round| html!(
+                        <li>
+                            { for round.counts.iter().map(|(id, count)| {
+                                let class = if round.eliminated.contains(id) {
+                                    "tag is-danger is-light mr-1"
+                                } else {
+                                    "tag is-light mr-1"
+                                };
+                                html!(<span class=class>{format!("{}: {}", details(id), count)}</span>)
+                            }) }
+                            { if round.exhausted > 0 {
+                                html!(<span class="tag is-white has-text-grey">{format!("{} exhausted", round.exhausted)}</span>)
+                            } else {
+                                html!()
+                            } }
+                            { if round.eliminated.is_empty() {
+                                html!()
+                            } else {
+                                html!(<p class="help">{format!(
+                                    "Knocked out: {}",
+                                    round.eliminated.iter().map(details).collect::<Vec<_>>().join(", ")
+                                )}</p>)
+                            } }
+                        </li>
+                    )) }
+                </ol>
+            </PanelBlock>
+        )
+    }
+
+    /// What participatory budgeting funds, for polls with funding.
+    fn show_funding(
+        &self,
+        results: &api::PollResults,
+        funding: Option<&api::tally::Funding>,
+    ) -> Html {
+        let budget = match results.poll.funding {
+            Some(budget) => budget,
+            None => return html!(),
+        };
+        let strategies = [
+            (api::tally::FundingStrategy::Greedy, "Most votes first"),
+            (api::tally::FundingStrategy::Optimal, "Most votes overall"),
+        ];
+        let summary = match funding {
+            Some(funding) => format!(
+                "{} proposal{} funded, {} of {} spent, {} left.",
+                funding.funded.len(),
+                if funding.funded.len() == 1 { "" } else { "s" },
+                funding.spent,
+                budget,
+                funding.left
+            ),
+            None => "There are too many ways to spend this budget to find the best one.".into(),
+        };
+        html!(
+            <PanelBlock style="display:block;">
+                <div class="level mb-2">
+                    <div class="level-left">
+                        <div class="level-item has-text-weight-semibold">{"Funding"}</div>
+                    </div>
+                    <div class="level-right">
+                        <div class="level-item">
+                            <div class="buttons has-addons are-small">
+                                { for strategies.iter().map(|(strategy, label)| {
+                                    let strategy = *strategy;
+                                    let class = if self.state.funding_strategy == strategy {
+                                        "button is-primary is-selected"
+                                    } else {
+                                        "button"
+                                    };
+                                    html!(<button class=class onclick=self.link.callback(move |_| Msg::ShowFunding(strategy))>{label}</button>)
+                                }) }
+                            </div>
+                        </div>
+                    </div>
+                </div>
+                <p>{summary}</p>
+            </PanelBlock>
+        )
+    }
+
+    /// Who on the roster has voted and who hasn't, for polls with one.
+    fn show_participation(&self, results: &api::PollResults) -> Html {
+        if results.participation.is_empty() {
+            return html!();
+        }
+        let (voted, waiting): (Vec<&api::Participant>, Vec<&api::Participant>) = results
+            .participation
+            .iter()
+            .partition(|participant| participant.voted);
+        let names = |participants: &[&api::Participant], class: &'static str| {
+            participants
+                .iter()
+                .map(|participant| html!(<span class=class>{&participant.name}</span>))
+                .collect::<Html>()
+        };
+        html!(
+            <PanelBlock style="display:block;">
+                <p class="has-text-weight-semibold mb-2">
+                    {format!("Participation: {} of {} voted", voted.len(), results.participation.len())}
+                </p>
+                { if voted.is_empty() {
+                    html!()
+                } else {
+                    html!(<p class="mb-1">
+                        <span class="mr-2">{"Voted"}</span>
+                        { names(&voted, "tag is-success is-light mr-1") }
+                    </p>)
+                } }
+                { if waiting.is_empty() {
+                    html!(<p class="help">{"Everyone has voted."}</p>)
+                } else {
+                    html!(<p>
+                        <span class="mr-2">{"Hasn't voted"}</span>
+                        { names(&waiting, "tag is-light mr-1") }
+                    </p>)
+                } }
+            </PanelBlock>
+        )
+    }
+
+    /// The standings split up by category, each with its own winners and shares.
+    fn show_categories(
+        &self,
+        results: &api::PollResults,
+        tally: &api::tally::Tally,
+        standings: &[Standing],
+        funding: Option<&api::tally::Funding>,
+    ) -> Html {
+        results
+            .poll
+            .group_choices(&results.choices)
+            .into_iter()
+            .map(|(category, choices)| {
+                let ids: Vec<api::ChoiceId> = choices.iter().map(|choice| choice.id).collect();
+                let name = category
+                    .and_then(|category| results.poll.categories.get(category))
+                    .map(|category| category.name.as_str())
+                    .unwrap_or("Other");
+                let winners: Vec<&str> = tally
+                    .winners_among(&ids)
+                    .iter()
+                    .filter_map(|id| choices.iter().find(|choice| choice.id == *id))
+                    .map(|choice| choice.details.as_str())
+                    .collect();
+                let group: Vec<&Standing> = standings
+                    .iter()
+                    .filter(|standing| ids.contains(&standing.choice.id))
+                    .collect();
+                let dots = group.iter().map(|standing| standing.positive).sum();
+                html!(
+                    <>
+                        <PanelBlock class="has-background-light">
+                            <strong class="mr-2">{name}</strong>
+                            { match winners.len() {
+                                0 => html!(<span class="tag is-light">{"No votes yet"}</span>),
+                                1 => html!(<span class="tag is-success is-light">{format!("Winner: {}", winners[0])}</span>),
+                                _ => html!(<span class="tag is-warning is-light">{format!("Tied: {}", winners.join(", "))}</span>),
+                            } }
+                        </PanelBlock>
+                        { for group.into_iter().map(|standing| self.show_choice(standing, dots, funding)) }
+                    </>
+                )
+            })
+            .collect()
+    }
+
+    /// A choice's dots all in the choice's colour and in no particular order. Anonymous polls
+    /// show them like this so they can't be traced back to ballots.
+    fn anonymous_dots(&self, standing: &Standing) -> Html {
+        let style = format!("color:{};", COLOURS[standing.index % COLOURS.len()]);
+        html!(
+            <div class="level-item">
+                { draw_dots(standing.positive, "icon", &style, "fas fa-circle") }
+                { draw_dots(standing.negative, "icon", &style, "fas fa-minus-circle") }
+            </div>
+        )
+    }
+
+    fn show_choice(
+        &self,
+        standing: &Standing,
+        dots: i32,
+        funding: Option<&api::tally::Funding>,
+    ) -> Html {
+        let choice = standing.choice;
+        let funded = funding.is_some_and(|funding| funding.funded.contains(&choice.id));
+        let (rank_class, rank_title) = if standing.tied {
+            ("tag is-warning is-light mr-2", "Tied")
+        } else {
+            ("tag is-light mr-2", "")
+        };
+        let rank = standing.place();
+        let percentage = standing.share(dots);
+        let results = self.state.results.as_ref().unwrap();
+        let method = results.poll.voting_method;
+        let choices = results.choices.len();
+        let drawn: i64 = results
             .votes
             .iter()
             .filter(|vote| vote.choice_id == choice.id)
+            .map(|vote| i64::from(api::tally::points(method, vote.dots, choices)).abs())
+            .sum();
+        // Too many to draw per voter, so fall back to the counts anonymous polls show
+        let counted = results.poll.anonymous || drawn > MAX_DRAWN_DOTS;
+        let votes: Vec<(&String, &'static str, &'static str, &'static str)> = results
+            .votes
+            .iter()
+            .filter(|vote| !counted && vote.choice_id == choice.id)
             .flat_map(|vote| {
                 let voter = &vote.voter;
-                (0..vote.dots).map(move |_| (voter, *self.state.voter_colours.get(voter).unwrap()))
+                let colour = *self.state.voter_colours.get(voter).unwrap();
+                let class = if self.state.fresh.contains(&vote.id) {
+                    "icon dot-arrived"
+                } else {
+                    "icon"
+                };
+                let points = api::tally::points(method, vote.dots, choices);
+                let icon = if points < 0 {
+                    "fas fa-minus-circle"
+                } else {
+                    "fas fa-circle"
+                };
+                (0..points.abs()).map(move |_| (voter, colour, class, icon))
             })
             .collect();
 
@@ -145,16 +959,32 @@ impl PollResults {
               <div class="level">
                 <div class="level-left">
                     <div class="level-item">
-                        <span class="panel-icon">
-                            <i class="fas fa-angle-right" aria-hidden="true"></i>
-                        </span>
+                        <span class=rank_class title=rank_title>{rank}</span>
                         {&choice.details}
+                        { match choice.cost {
+                            Some(cost) => html!(<span class="tag is-light ml-2">{format!("Costs {}", cost)}</span>),
+                            None => html!(),
+                        } }
+                        { if funded { html!(<span class="tag is-success ml-2">{"Funded"}</span>) } else { html!() } }
                     </div>
                 </div>
                 <div class="level-right">
-                    <div class="level-item">
-                        { for votes.iter().map(|c| html!(<span class="icon" style={format!("color:{};", c.1)} data-tooltip={c.0}><i class="fas fa-circle"></i></span>)) }
+                    <div class="level-item has-text-grey">
+                        { if method == api::VotingMethod::Quadratic {
+                            format!("{} {}, {} credits, {:.1}%", standing.total, unit(method, standing.total), standing.credits, percentage)
+                        } else if results.poll.negative_dots().is_some() {
+                            format!("+{} / -{}, net {} {}, {:.1}%", standing.positive, standing.negative, standing.total, unit(method, standing.total), percentage)
+                        } else {
+                            format!("{} {}, {:.1}%", standing.total, unit(method, standing.total), percentage)
+                        } }
                     </div>
+                    { if counted {
+                        self.anonymous_dots(standing)
+                    } else { html!(
+                        <div class="level-item">
+                            { for votes.iter().map(|c| html!(<span class={c.2} style={format!("color:{};", c.1)} data-tooltip={c.0}><i class={c.3}></i></span>)) }
+                        </div>
+                    ) } }
                 </div>
               </div>
             </PanelBlock>
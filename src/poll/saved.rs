@@ -0,0 +1,208 @@
+//! What this browser remembers about polls, kept in localStorage.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use yew::format::Json;
+use yew::services::storage::StorageService;
+
+use crate::api;
+
+/// A ballot cast from this browser, so it can be shown, changed or withdrawn later.
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct SavedBallot {
+    pub voter: String,
+    pub choices: HashMap<api::ChoiceId, i32>,
+    pub edit_token: Option<api::EditToken>,
+}
+
+pub(super) fn ballot_key(poll_id: &str) -> String {
+    format!("com.dotdotyew.votes.{}", poll_id)
+}
+
+pub(super) fn restore_ballot(storage: &StorageService, poll_id: &str) -> Option<SavedBallot> {
+    let key = ballot_key(poll_id);
+    if let Json(Ok(ballot)) = storage.restore(&key) {
+        return Some(ballot);
+    }
+    // Ballots saved before votes could be changed are just the choices
+    if let Json(Ok(choices)) = storage.restore(&key) {
+        return Some(SavedBallot {
+            voter: "".into(),
+            choices,
+            edit_token: None,
+        });
+    }
+    None
+}
+
+fn admin_key(poll_id: &str) -> String {
+    format!("com.dotdotyew.admin.{}", poll_id)
+}
+
+/// The admin token for a poll created from this browser.
+pub(super) fn restore_admin_token(
+    storage: &StorageService,
+    poll_id: &str,
+) -> Option<api::AdminToken> {
+    match storage.restore(&admin_key(poll_id)) {
+        Json(Ok(token)) => Some(token),
+        _ => None,
+    }
+}
+
+pub(super) fn store_admin_token(
+    storage: &mut StorageService,
+    poll_id: &str,
+    token: &api::AdminToken,
+) {
+    storage.store(&admin_key(poll_id), Json(token));
+}
+
+pub(super) fn remove_admin_token(storage: &mut StorageService, poll_id: &str) {
+    storage.remove(&admin_key(poll_id));
+}
+
+fn invite_key(poll_id: &str) -> String {
+    format!("com.dotdotyew.invite.{}", poll_id)
+}
+
+/// The invite this browser was opened with for a poll with a roster.
+pub(super) fn restore_invite(storage: &StorageService, poll_id: &str) -> Option<api::InviteToken> {
+    match storage.restore(&invite_key(poll_id)) {
+        Json(Ok(token)) => Some(token),
+        _ => None,
+    }
+}
+
+pub(super) fn store_invite(storage: &mut StorageService, poll_id: &str, token: &api::InviteToken) {
+    storage.store(&invite_key(poll_id), Json(token));
+}
+
+/// The token that shows this browser a poll's results if they're hidden from others: its admin
+/// token, or else the edit token of the ballot cast here.
+pub(super) fn results_token(storage: &StorageService, poll_id: &str) -> Option<String> {
+    restore_admin_token(storage, poll_id)
+        .map(|token| token.0)
+        .or_else(|| {
+            restore_ballot(storage, poll_id)
+                .and_then(|ballot| ballot.edit_token)
+                .map(|token| token.0)
+        })
+}
+
+const HISTORY_KEY: &str = "com.dotdotyew.history";
+
+/// A poll this browser created or voted in, for the "My polls" page. Whether it was created or
+/// voted in here is down to whether its admin token or ballot are still saved.
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct SavedPoll {
+    pub poll: api::PollUuid,
+    pub title: String,
+    pub last_seen: api::Timestamp,
+}
+
+/// Every poll in this browser's history, most recently seen first.
+pub(super) fn restore_history(storage: &StorageService) -> Vec<SavedPoll> {
+    let mut history: Vec<SavedPoll> = match storage.restore(HISTORY_KEY) {
+        Json(Ok(history)) => history,
+        _ => Vec::new(),
+    };
+    history.sort_by_key(|saved| std::cmp::Reverse(saved.last_seen));
+    history
+}
+
+fn store_history(storage: &mut StorageService, history: &[SavedPoll]) {
+    storage.store(HISTORY_KEY, Json(&history));
+}
+
+/// Adds a poll to the history, or brings its title up to date.
+pub(super) fn remember_poll(storage: &mut StorageService, poll: &api::PollUuid, title: &str) {
+    let mut history = restore_history(storage);
+    history.retain(|saved| saved.poll != *poll);
+    history.push(SavedPoll {
+        poll: poll.clone(),
+        title: title.to_owned(),
+        last_seen: crate::time::now(),
+    });
+    store_history(storage, &history);
+}
+
+/// Drops a poll from the history, along with its ballot and admin token.
+pub(super) fn forget_poll(storage: &mut StorageService, poll_id: &str) {
+    let mut history = restore_history(storage);
+    history.retain(|saved| saved.poll.as_str() != poll_id);
+    store_history(storage, &history);
+    storage.remove(&ballot_key(poll_id));
+    storage.remove(&invite_key(poll_id));
+    remove_admin_token(storage, poll_id);
+}
+
+/// The history as exported to move it to another browser, secrets included.
+#[derive(Serialize, Deserialize)]
+pub(super) struct ExportedHistory {
+    pub polls: Vec<ExportedPoll>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct ExportedPoll {
+    #[serde(flatten)]
+    pub saved: SavedPoll,
+    #[serde(default)]
+    pub ballot: Option<SavedBallot>,
+    #[serde(default)]
+    pub admin_token: Option<api::AdminToken>,
+    #[serde(default)]
+    pub invite: Option<api::InviteToken>,
+}
+
+pub(super) fn export_history(storage: &StorageService) -> ExportedHistory {
+    ExportedHistory {
+        polls: restore_history(storage)
+            .into_iter()
+            .map(|saved| ExportedPoll {
+                ballot: restore_ballot(storage, saved.poll.as_str()),
+                admin_token: restore_admin_token(storage, saved.poll.as_str()),
+                invite: restore_invite(storage, saved.poll.as_str()),
+                saved,
+            })
+            .collect(),
+    }
+}
+
+/// Merges an exported history into this browser's, returning how many polls were new. Ballots,
+/// admin tokens and invites already saved here are kept.
+pub(super) fn import_history(storage: &mut StorageService, imported: ExportedHistory) -> usize {
+    let mut history = restore_history(storage);
+    let mut added = 0;
+    for poll in imported.polls {
+        let poll_id = poll.saved.poll.as_str().to_owned();
+        match history
+            .iter_mut()
+            .find(|saved| saved.poll == poll.saved.poll)
+        {
+            Some(saved) if saved.last_seen < poll.saved.last_seen => *saved = poll.saved,
+            Some(_) => {}
+            None => {
+                history.push(poll.saved);
+                added += 1;
+            }
+        }
+        if let Some(ballot) = poll.ballot {
+            if restore_ballot(storage, &poll_id).is_none() {
+                storage.store(&ballot_key(&poll_id), Json(&ballot));
+            }
+        }
+        if let Some(token) = poll.admin_token {
+            if restore_admin_token(storage, &poll_id).is_none() {
+                store_admin_token(storage, &poll_id, &token);
+            }
+        }
+        if let Some(token) = poll.invite {
+            if restore_invite(storage, &poll_id).is_none() {
+                store_invite(storage, &poll_id, &token);
+            }
+        }
+    }
+    store_history(storage, &history);
+    added
+}
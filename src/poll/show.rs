@@ -1,37 +1,108 @@
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::time::Duration;
 
+use super::results::{capitalise, draw_dots};
+use super::saved::{self, SavedBallot};
 use crate::api;
-use crate::component::{Panel, PanelBlock, PanelHeading};
-use yew::events::MouseEvent;
+use crate::component::{ErrorBlock, ErrorPanel, Panel, PanelBlock, PanelHeading};
+use crate::time;
+use yew::events::{DragEvent, MouseEvent};
 use yew::format::Json;
 use yew::prelude::*;
-use yew::services::fetch::FetchTask;
+use yew::services::interval::{IntervalService, IntervalTask};
 use yew::services::storage::{Area, StorageService};
 use yew_router::prelude::*;
 
+/// How often to check a brainstorming poll for new choices and phase changes.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Properties, Clone, PartialEq)]
 pub struct Props {
     // required
     pub poll_id: String,
+    // From an invite link, for polls with a roster
+    #[prop_or_default]
+    pub invite: Option<String>,
 }
 
 struct State {
     voted: bool,
+    // Set once a ballot has been cast from here, until it's withdrawn. Submitting while not
+    // `voted` changes that ballot rather than casting another.
+    edit_token: Option<api::EditToken>,
     poll: Option<api::Poll>,
-    votes: HashMap<i32, i32>,
+    votes: HashMap<api::ChoiceId, i32>,
+    // The choices in ranked order, for ranked voting methods
+    ranking: Vec<api::ChoiceId>,
+    dragging: Option<usize>,
     name: String,
-    dots_remaining: i32,
+    // The invite this browser votes with, who it's for once fetched, and any problem fetching it
+    invite: Option<api::InviteToken>,
+    invitee: Option<api::Participant>,
+    invite_error: Option<api::ApiError>,
+    // Dots left to place against choices, when the poll allows it
+    against_remaining: i32,
+    // The choice a dot was last refused on, and why
+    rejected: Option<(api::ChoiceId, String)>,
+    error: Option<api::ApiError>,
+    vote_error: Option<api::ApiError>,
+    submitting: bool,
+    // What opening and closing times are measured against, ticking along while they matter
+    now: api::Timestamp,
+    // Set when this browser created the poll
+    admin_token: Option<api::AdminToken>,
+    // Closing the poll or moving it to its next phase
+    admin_busy: bool,
+    admin_error: Option<api::ApiError>,
+    // Brainstorming: the choice being written and its category, and any problem adding the
+    // last one
+    new_choice: String,
+    new_category: Option<usize>,
+    adding: bool,
+    add_error: Option<api::ApiError>,
+    // The facilitator's choices ticked to merge, and the merges to make when voting starts
+    merging: Vec<api::ChoiceId>,
+    merges: Vec<api::MergeChoices>,
 }
 
 pub enum Msg {
-    AddDot(i32),
-    RemoveDot(i32),
+    AddDot(api::ChoiceId),
+    AddDotAgainst(api::ChoiceId),
+    RemoveDot(api::ChoiceId),
+    ToggleApproval(api::ChoiceId),
+    SetScore(api::ChoiceId, i32),
+    MoveRank(usize, usize),
+    DragStart(usize),
+    DropAt(usize),
     UpdateName(String),
+    InviteLoaded(Result<api::Participant, api::ApiError>),
     FetchSuccess(api::Poll),
     SubmitVote,
-    FetchFailed,
-    VoteSuccess,
-    VoteFailed,
+    FetchFailed(api::ApiError),
+    Retry,
+    VoteSuccess(api::EditToken),
+    VoteFailed(api::ApiError),
+    ChangeVote,
+    CancelChange,
+    WithdrawVote,
+    WithdrawSuccess,
+    Tick,
+    ClosePoll,
+    AdminSuccess(api::Poll),
+    AdminFailed(api::ApiError),
+    Refresh,
+    Refreshed(Result<api::Poll, api::ApiError>),
+    UpdateNewChoice(String),
+    UpdateNewCategory(Option<usize>),
+    AddChoice,
+    AddSuccess(api::Poll),
+    AddFailed(api::ApiError),
+    ToggleMerge(api::ChoiceId),
+    MergeSelected,
+    UndoMerges,
+    AdvancePhase(api::PollPhase),
 }
 
 pub struct ShowPoll {
@@ -39,7 +110,16 @@ pub struct ShowPoll {
     storage: StorageService,
     state: State,
     props: Props,
-    tasks: Vec<FetchTask>,
+    backend: Rc<dyn api::PollBackend>,
+    // One request of each kind at a time. Starting another drops, and so cancels, the last.
+    fetching: Option<api::ApiTask>,
+    fetching_invite: Option<api::ApiTask>,
+    managing: Option<api::ApiTask>,
+    adding: Option<api::ApiTask>,
+    voting: Option<api::ApiTask>,
+    _clock: Option<IntervalTask>,
+    _refresh: Option<IntervalTask>,
+    refreshing: Option<api::ApiTask>,
 }
 
 impl Component for ShowPoll {
@@ -50,35 +130,44 @@ impl Component for ShowPoll {
         let storage = StorageService::new(Area::Local).expect("browser storage disabled");
 
         // On creation, start fetching the poll from the backend
-        let task = api::get_poll(&props.poll_id, &link, |response| {
-            if let (meta, Json(Ok(body))) = response.into_parts() {
-                if meta.status.is_success() {
-                    return Msg::FetchSuccess(body);
-                }
-            }
-            Msg::FetchFailed
-        });
+        let backend = api::backend();
+        let fetching = Self::fetch(&backend, &props.poll_id, &link);
+        let invite = Self::invite(&storage, &props);
+        let fetching_invite = invite
+            .as_ref()
+            .map(|invite| Self::fetch_invite(&backend, &props.poll_id, invite, &link));
 
-        let state = {
-            if let Json(Ok(votes)) =
-                storage.restore(&format!("com.dotdotyew.votes.{}", &props.poll_id))
-            {
-                State {
-                    poll: None,
-                    name: "".into(),
-                    votes,
-                    dots_remaining: 0,
-                    voted: true,
-                }
-            } else {
-                State {
-                    poll: None,
-                    name: "".into(),
-                    votes: HashMap::new(),
-                    dots_remaining: 2,
-                    voted: false,
-                }
-            }
+        let saved = saved::restore_ballot(&storage, &props.poll_id);
+        let state = State {
+            poll: None,
+            voted: saved.is_some(),
+            name: saved
+                .as_ref()
+                .map(|ballot| ballot.voter.clone())
+                .unwrap_or_default(),
+            edit_token: saved.as_ref().and_then(|ballot| ballot.edit_token.clone()),
+            votes: saved.map(|ballot| ballot.choices).unwrap_or_default(),
+            ranking: Vec::new(),
+            dragging: None,
+            invite,
+            invitee: None,
+            invite_error: None,
+            // Filled in from the poll's rules once it has been fetched
+            against_remaining: 0,
+            rejected: None,
+            error: None,
+            vote_error: None,
+            submitting: false,
+            now: time::now(),
+            admin_token: saved::restore_admin_token(&storage, &props.poll_id),
+            admin_busy: false,
+            admin_error: None,
+            new_choice: "".into(),
+            new_category: None,
+            adding: false,
+            add_error: None,
+            merging: Vec::new(),
+            merges: Vec::new(),
         };
 
         Self {
@@ -86,72 +175,406 @@ impl Component for ShowPoll {
             storage,
             props,
             state,
-            tasks: vec![task],
+            backend,
+            fetching: Some(fetching),
+            fetching_invite,
+            managing: None,
+            adding: None,
+            voting: None,
+            _clock: None,
+            _refresh: None,
+            refreshing: None,
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::FetchSuccess(poll) => {
+                self.count_remaining(&poll);
+                if poll.poll.voting_method.is_ranked() {
+                    // Ranked choices first, in order, then everything else as listed
+                    let mut ranking: Vec<&api::PollChoice> = poll.choices.iter().collect();
+                    ranking.sort_by_key(|choice| match self.state.votes.get(&choice.id) {
+                        Some(rank) if *rank > 0 => *rank,
+                        _ => i32::MAX,
+                    });
+                    self.state.ranking = ranking.iter().map(|choice| choice.id).collect();
+                    if !self.state.voted {
+                        self.rank_votes();
+                    }
+                }
+                self.state.now = time::now();
+                self.start_clock(&poll);
+                // Brainstorming polls change under us, as choices come in and phases move on
+                self._refresh = match poll.poll.phase {
+                    Some(api::PollPhase::Collect) | Some(api::PollPhase::Vote) => {
+                        Some(IntervalService::spawn(
+                            REFRESH_INTERVAL,
+                            self.link.callback(|_| Msg::Refresh),
+                        ))
+                    }
+                    _ => None,
+                };
+                // Keeps titles in "My polls" current, and picks up polls saved before it existed
+                if self.state.voted || self.state.admin_token.is_some() {
+                    saved::remember_poll(&mut self.storage, &poll.poll.uuid, &poll.poll.title);
+                }
                 self.state.poll = Some(poll);
                 true
             }
-            Msg::FetchFailed => {
-                // TODO: Error handling
+            Msg::Tick => {
+                self.state.now = time::now();
+                let status = match &self.state.poll {
+                    Some(poll) => poll.poll.status(self.state.now),
+                    None => return false,
+                };
+                if status == api::PollStatus::Closed {
+                    self._clock = None;
+                    // A change that didn't make it in time doesn't count
+                    if !self.state.voted && self.state.edit_token.is_some() {
+                        return self.update(Msg::CancelChange);
+                    }
+                }
+                true
+            }
+            Msg::ClosePoll => {
+                let token = match self.state.admin_token.clone() {
+                    Some(token) => token,
+                    None => return false,
+                };
+                let backend = self.backend.clone();
+                let poll_id = api::PollUuid::from(self.props.poll_id.clone());
+                let task = api::spawn(&self.link, async move {
+                    match backend.close_poll(&poll_id, &token).await {
+                        Ok(poll) => Msg::AdminSuccess(poll),
+                        Err(error) => Msg::AdminFailed(error),
+                    }
+                });
+                self.state.admin_busy = true;
+                self.state.admin_error = None;
+                self.managing = Some(task);
+                true
+            }
+            Msg::AdvancePhase(phase) => {
+                let token = match self.state.admin_token.clone() {
+                    Some(token) => token,
+                    None => return false,
+                };
+                let advance = api::AdvancePhase {
+                    phase,
+                    merges: self.state.merges.clone(),
+                };
+                let backend = self.backend.clone();
+                let poll_id = api::PollUuid::from(self.props.poll_id.clone());
+                let task = api::spawn(&self.link, async move {
+                    match backend.advance_phase(&poll_id, &token, &advance).await {
+                        Ok(poll) => Msg::AdminSuccess(poll),
+                        Err(error) => Msg::AdminFailed(error),
+                    }
+                });
+                self.state.admin_busy = true;
+                self.state.admin_error = None;
+                self.managing = Some(task);
+                true
+            }
+            Msg::AdminSuccess(poll) => {
+                self.state.admin_busy = false;
+                self.state.merging.clear();
+                self.state.merges.clear();
+                self.update(Msg::FetchSuccess(poll));
+                self.update(Msg::Tick)
+            }
+            Msg::AdminFailed(error) => {
+                self.state.admin_busy = false;
+                self.state.admin_error = Some(error);
+                true
+            }
+            Msg::Refresh => {
+                let backend = self.backend.clone();
+                let poll_id = api::PollUuid::from(self.props.poll_id.clone());
+                self.refreshing = Some(api::spawn(&self.link, async move {
+                    Msg::Refreshed(backend.get_poll(&poll_id).await)
+                }));
                 false
             }
+            Msg::Refreshed(poll) => match poll {
+                Ok(poll) if self.state.poll.as_ref() != Some(&poll) => {
+                    self.update(Msg::FetchSuccess(poll))
+                }
+                // Unchanged, or failed and tried again next time round
+                _ => false,
+            },
+            Msg::UpdateNewChoice(value) => {
+                self.state.new_choice = value;
+                true
+            }
+            Msg::UpdateNewCategory(category) => {
+                self.state.new_category = category;
+                true
+            }
+            Msg::AddChoice => {
+                let choice = api::NewChoice {
+                    details: self.state.new_choice.clone(),
+                    category: self.state.new_category,
+                };
+                let backend = self.backend.clone();
+                let poll_id = api::PollUuid::from(self.props.poll_id.clone());
+                let task = api::spawn(&self.link, async move {
+                    match backend.add_choice(&poll_id, &choice).await {
+                        Ok(poll) => Msg::AddSuccess(poll),
+                        Err(error) => Msg::AddFailed(error),
+                    }
+                });
+                self.state.adding = true;
+                self.state.add_error = None;
+                self.adding = Some(task);
+                true
+            }
+            Msg::AddSuccess(poll) => {
+                self.state.adding = false;
+                self.state.new_choice.clear();
+                self.update(Msg::FetchSuccess(poll))
+            }
+            Msg::AddFailed(error) => {
+                self.state.adding = false;
+                self.state.add_error = Some(error);
+                true
+            }
+            Msg::ToggleMerge(choice) => {
+                if let Some(i) = self.state.merging.iter().position(|id| *id == choice) {
+                    self.state.merging.remove(i);
+                } else {
+                    self.state.merging.push(choice);
+                }
+                true
+            }
+            Msg::MergeSelected => {
+                self.merge_selected();
+                true
+            }
+            Msg::UndoMerges => {
+                self.state.merging.clear();
+                self.state.merges.clear();
+                true
+            }
+            Msg::FetchFailed(error) => {
+                self.state.error = Some(error);
+                true
+            }
+            Msg::Retry => {
+                self.state.error = None;
+                let task = Self::fetch(&self.backend, &self.props.poll_id, &self.link);
+                self.fetching = Some(task);
+                true
+            }
             Msg::UpdateName(value) => {
                 self.state.name = value;
                 true
             }
+            Msg::InviteLoaded(Ok(invitee)) => {
+                if let Some(invite) = &self.state.invite {
+                    saved::store_invite(&mut self.storage, &self.props.poll_id, invite);
+                }
+                self.state.name = invitee.name.clone();
+                self.state.invitee = Some(invitee);
+                true
+            }
+            Msg::InviteLoaded(Err(error)) => {
+                self.state.invite_error = Some(error);
+                true
+            }
             Msg::AddDot(id) => {
-                if self.state.dots_remaining == 0 {
+                if self.locked() || self.choice_remaining(id) == 0 {
                     return false;
                 }
-                *self.state.votes.entry(id).or_insert(0) += 1;
-                self.state.dots_remaining -= 1;
+                if let Err(reason) = self.check_dot(id) {
+                    self.state.rejected = Some((id, reason));
+                    return true;
+                }
+                let votes = self.state.votes.get(&id).cloned().unwrap_or(0) + 1;
+                self.state.votes.insert(id, votes);
+                self.state.rejected = None;
                 true
             }
-            Msg::RemoveDot(id) => match self.state.votes.get_mut(&id) {
-                Some(v) => {
-                    if *v >= 1 {
-                        *v -= 1;
-                        self.state.dots_remaining += 1;
-                        true
-                    } else {
-                        false
-                    }
+            Msg::AddDotAgainst(id) => {
+                if self.locked() || self.state.against_remaining == 0 {
+                    return false;
+                }
+                let votes = self.state.votes.get(&id).cloned().unwrap_or(0);
+                if votes > 0 {
+                    self.state.rejected = Some((id, "Take your dots off this choice first".into()));
+                    return true;
+                }
+                self.state.against_remaining -= 1;
+                self.state.votes.insert(id, votes - 1);
+                self.state.rejected = None;
+                true
+            }
+            Msg::RemoveDot(_) if self.locked() => false,
+            Msg::RemoveDot(id) => match self.state.votes.get(&id).cloned() {
+                Some(votes) if votes >= 1 => {
+                    self.state.votes.insert(id, votes - 1);
+                    self.state.rejected = None;
+                    true
+                }
+                Some(votes) if votes < 0 => {
+                    self.state.against_remaining += 1;
+                    self.state.votes.insert(id, votes + 1);
+                    self.state.rejected = None;
+                    true
+                }
+                _ => false,
+            },
+            Msg::ToggleApproval(id) => {
+                if self.locked() {
+                    return false;
+                }
+                let approved = self.state.votes.entry(id).or_insert(0);
+                *approved = 1 - *approved;
+                true
+            }
+            Msg::SetScore(id, score) => {
+                if self.locked() {
+                    return false;
                 }
+                self.state.votes.insert(id, score.clamp(0, api::MAX_SCORE));
+                true
+            }
+            Msg::MoveRank(from, to) => {
+                if self.locked() || from >= self.state.ranking.len() {
+                    return false;
+                }
+                let id = self.state.ranking.remove(from);
+                let to = to.min(self.state.ranking.len());
+                self.state.ranking.insert(to, id);
+                self.rank_votes();
+                true
+            }
+            Msg::DragStart(i) => {
+                self.state.dragging = Some(i);
+                false
+            }
+            Msg::DropAt(to) => match self.state.dragging.take() {
+                Some(from) => self.update(Msg::MoveRank(from, to)),
                 None => false,
             },
             Msg::SubmitVote => {
-                let task = api::vote(
-                    &self.props.poll_id,
-                    &self.state.name,
-                    self.state.votes.clone(),
-                    &self.link,
-                    |response| {
-                        let (meta, _) = response.into_parts();
-                        if meta.status.is_success() {
-                            return Msg::VoteSuccess;
-                        }
-                        Msg::VoteFailed
+                let backend = self.backend.clone();
+                let poll_id = api::PollUuid::from(self.props.poll_id.clone());
+                let anonymous = self
+                    .state
+                    .poll
+                    .as_ref()
+                    .is_some_and(|poll| poll.poll.anonymous);
+                let vote = api::VoteSubmission {
+                    voter: if anonymous {
+                        String::new()
+                    } else {
+                        self.state.name.clone()
                     },
-                );
-                self.tasks.push(task);
+                    choices: self.state.votes.clone(),
+                    invite: self.state.invite.clone(),
+                };
+                let edit_token = self.state.edit_token.clone();
+                let task = api::spawn(&self.link, async move {
+                    let sent = match edit_token {
+                        Some(token) => backend
+                            .change_vote(&poll_id, &token, &vote)
+                            .await
+                            .map(|()| token),
+                        None => backend
+                            .vote(&poll_id, &vote)
+                            .await
+                            .map(|receipt| receipt.edit_token),
+                    };
+                    match sent {
+                        Ok(token) => Msg::VoteSuccess(token),
+                        Err(error) => Msg::VoteFailed(error),
+                    }
+                });
+                self.state.submitting = true;
+                self.state.vote_error = None;
+                self.voting = Some(task);
                 true
             }
-            Msg::VoteSuccess => {
+            Msg::VoteSuccess(token) => {
                 self.state.voted = true;
+                self.state.submitting = false;
+                self.state.edit_token = Some(token);
+                if let Some(invitee) = &mut self.state.invitee {
+                    invitee.voted = true;
+                }
 
                 self.storage.store(
-                    &format!("com.dotdotyew.votes.{}", &self.props.poll_id),
-                    Json(&self.state.votes),
+                    &saved::ballot_key(&self.props.poll_id),
+                    Json(&SavedBallot {
+                        voter: self.state.name.trim().to_owned(),
+                        choices: self.state.votes.clone(),
+                        edit_token: self.state.edit_token.clone(),
+                    }),
                 );
+                if let Some(poll) = &self.state.poll {
+                    saved::remember_poll(&mut self.storage, &poll.poll.uuid, &poll.poll.title);
+                }
+                true
+            }
+            Msg::ChangeVote => {
+                self.state.voted = false;
+                self.state.vote_error = None;
+                true
+            }
+            Msg::CancelChange => {
+                if let Some(ballot) = saved::restore_ballot(&self.storage, &self.props.poll_id) {
+                    self.state.name = ballot.voter;
+                    self.state.votes = ballot.choices;
+                }
+                self.state.voted = true;
+                self.state.rejected = None;
+                self.state.vote_error = None;
+                self.refresh_ballot();
                 true
             }
-            Msg::VoteFailed => {
-                // TODO
+            Msg::WithdrawVote => {
+                let token = match self.state.edit_token.clone() {
+                    Some(token) => token,
+                    None => return false,
+                };
+                let backend = self.backend.clone();
+                let poll_id = api::PollUuid::from(self.props.poll_id.clone());
+                let task = api::spawn(&self.link, async move {
+                    match backend.withdraw_vote(&poll_id, &token).await {
+                        Ok(()) => Msg::WithdrawSuccess,
+                        Err(error) => Msg::VoteFailed(error),
+                    }
+                });
+                self.state.submitting = true;
+                self.state.vote_error = None;
+                self.voting = Some(task);
+                true
+            }
+            Msg::WithdrawSuccess => {
+                self.storage.remove(&saved::ballot_key(&self.props.poll_id));
+                self.state.voted = false;
+                self.state.submitting = false;
+                self.state.edit_token = None;
+                if let Some(invitee) = &mut self.state.invitee {
+                    invitee.voted = false;
+                }
+                self.state.votes.clear();
+                self.refresh_ballot();
+                if self
+                    .state
+                    .poll
+                    .as_ref()
+                    .is_some_and(|poll| poll.poll.voting_method.is_ranked())
+                {
+                    self.rank_votes();
+                }
+                true
+            }
+            Msg::VoteFailed(error) => {
+                self.state.submitting = false;
+                self.state.vote_error = Some(error);
                 true
             }
         }
@@ -161,26 +584,29 @@ impl Component for ShowPoll {
         if props != self.props {
             // Haven't tested this code path, but I think we just want to refetch when this happens
             self.state.poll = None;
-            let task = api::get_poll(&props.poll_id, &self.link, |response| {
-                if let (meta, Json(Ok(body))) = response.into_parts() {
-                    if meta.status.is_success() {
-                        return Msg::FetchSuccess(body);
-                    }
-                }
-                Msg::FetchFailed
+            self.state.error = None;
+            self.fetching = Some(Self::fetch(&self.backend, &props.poll_id, &self.link));
+            self.state.invite = Self::invite(&self.storage, &props);
+            self.state.invitee = None;
+            self.state.invite_error = None;
+            self.fetching_invite = self.state.invite.as_ref().map(|invite| {
+                Self::fetch_invite(&self.backend, &props.poll_id, invite, &self.link)
             });
-            self.tasks.push(task);
+            self.props = props;
             return true;
         }
         false
     }
 
     fn view(&self) -> Html {
-        if let Some(poll) = &self.state.poll {
-            if self.state.voted {
-                self.show_voted(poll)
-            } else {
-                self.show_can_vote(poll)
+        if let Some(error) = &self.state.error {
+            html!(<ErrorPanel error=error.clone() onretry=self.link.callback(|_| Msg::Retry) />)
+        } else if let Some(poll) = &self.state.poll {
+            match poll.poll.status(self.state.now) {
+                api::PollStatus::Open if poll.poll.collecting() => self.show_collect(poll),
+                api::PollStatus::Open if self.state.voted => self.show_voted(poll),
+                api::PollStatus::Open => self.show_can_vote(poll),
+                status => self.show_locked(poll, status),
             }
         } else {
             html!(
@@ -193,8 +619,332 @@ impl Component for ShowPoll {
 }
 
 impl ShowPoll {
+    fn fetch(
+        backend: &Rc<dyn api::PollBackend>,
+        poll_id: &str,
+        link: &ComponentLink<Self>,
+    ) -> api::ApiTask {
+        let backend = backend.clone();
+        let poll_id = api::PollUuid::from(poll_id);
+        api::spawn(link, async move {
+            match backend.get_poll(&poll_id).await {
+                Ok(poll) => Msg::FetchSuccess(poll),
+                Err(error) => Msg::FetchFailed(error),
+            }
+        })
+    }
+
+    /// The invite from the link this page was opened with, or else one saved from an earlier
+    /// visit.
+    fn invite(storage: &StorageService, props: &Props) -> Option<api::InviteToken> {
+        props
+            .invite
+            .clone()
+            .map(api::InviteToken)
+            .or_else(|| saved::restore_invite(storage, &props.poll_id))
+    }
+
+    fn fetch_invite(
+        backend: &Rc<dyn api::PollBackend>,
+        poll_id: &str,
+        invite: &api::InviteToken,
+        link: &ComponentLink<Self>,
+    ) -> api::ApiTask {
+        let backend = backend.clone();
+        let poll_id = api::PollUuid::from(poll_id);
+        let invite = invite.clone();
+        api::spawn(link, async move {
+            Msg::InviteLoaded(backend.get_invite(&poll_id, &invite).await)
+        })
+    }
+
+    /// Ticks every second while the poll has an opening or closing time still to come, so the
+    /// countdown moves and the ballot locks when it's reached.
+    fn start_clock(&mut self, poll: &api::Poll) {
+        let now = self.state.now;
+        let upcoming = [poll.poll.opens_at, poll.poll.closes_at]
+            .iter()
+            .any(|time| time.is_some_and(|time| time > now));
+        self._clock = if upcoming {
+            Some(IntervalService::spawn(
+                Duration::from_secs(1),
+                self.link.callback(|_| Msg::Tick),
+            ))
+        } else {
+            None
+        };
+    }
+
+    /// Whether the ballot can't be touched, because it's been sent or the poll isn't taking
+    /// votes.
+    fn locked(&self) -> bool {
+        self.state.voted
+            || self
+                .state
+                .poll
+                .as_ref()
+                .is_some_and(|poll| !poll.poll.taking_votes(self.state.now))
+    }
+
+    /// Folds the ticked choices, and anything already merged into them, into whichever of them
+    /// came first.
+    fn merge_selected(&mut self) {
+        let poll = match &self.state.poll {
+            Some(poll) => poll,
+            None => return,
+        };
+        let mut selected: Vec<api::ChoiceId> = self.state.merging.drain(..).collect();
+        let (absorbed, kept): (Vec<_>, Vec<_>) = self
+            .state
+            .merges
+            .drain(..)
+            .partition(|merge| selected.contains(&merge.into));
+        for merge in absorbed {
+            selected.extend(merge.from);
+        }
+        self.state.merges = kept;
+        let into = match poll
+            .choices
+            .iter()
+            .find(|choice| selected.contains(&choice.id))
+        {
+            Some(choice) => choice.id,
+            None => return,
+        };
+        selected.retain(|id| *id != into);
+        if !selected.is_empty() {
+            self.state.merges.push(api::MergeChoices {
+                into,
+                from: selected,
+            });
+        }
+    }
+
+    /// The choice `choice` is being merged into, if any.
+    fn merged_into<'a>(
+        &self,
+        poll: &'a api::Poll,
+        choice: api::ChoiceId,
+    ) -> Option<&'a api::PollChoice> {
+        let merge = self
+            .state
+            .merges
+            .iter()
+            .find(|merge| merge.from.contains(&choice))?;
+        poll.choices.iter().find(|choice| choice.id == merge.into)
+    }
+
+    /// Works out the dots left to place against choices from the votes already on the ballot,
+    /// which are only there when showing or changing an earlier vote.
+    fn count_remaining(&mut self, poll: &api::Poll) {
+        let against: i32 = self.state.votes.values().filter(|votes| **votes < 0).sum();
+        self.state.against_remaining = poll.poll.negative_dots().unwrap_or(0) + against;
+    }
+
+    /// The dots, or credits on quadratic polls, left to place from `budget`.
+    fn dots_remaining(&self, poll: &api::Poll, budget: Option<usize>) -> i32 {
+        let quadratic = poll.poll.voting_method == api::VotingMethod::Quadratic;
+        let spent: i32 = poll
+            .choices
+            .iter()
+            .filter(|choice| poll.budget_of(choice) == budget)
+            .filter_map(|choice| self.state.votes.get(&choice.id))
+            .filter(|votes| **votes > 0)
+            .map(|votes| {
+                if quadratic {
+                    api::quadratic_cost(*votes)
+                } else {
+                    *votes
+                }
+            })
+            .sum();
+        poll.budget_dots(budget) - spent
+    }
+
+    /// The dots left across every budget.
+    fn total_remaining(&self, poll: &api::Poll) -> i32 {
+        poll.budgets()
+            .into_iter()
+            .map(|budget| self.dots_remaining(poll, budget))
+            .sum()
+    }
+
+    /// The dots left in the budget choice `id` is paid from.
+    fn choice_remaining(&self, id: api::ChoiceId) -> i32 {
+        let poll = match &self.state.poll {
+            Some(poll) => poll,
+            None => return 0,
+        };
+        match poll.choices.iter().find(|choice| choice.id == id) {
+            Some(choice) => self.dots_remaining(poll, poll.budget_of(choice)),
+            None => 0,
+        }
+    }
+
+    fn refresh_ballot(&mut self) {
+        if let Some(poll) = self.state.poll.take() {
+            self.count_remaining(&poll);
+            self.state.poll = Some(poll);
+        }
+    }
+
+    /// Checks whether one more dot may be placed on choice `id` under the poll's rules.
+    fn check_dot(&self, id: api::ChoiceId) -> Result<(), String> {
+        let poll = match &self.state.poll {
+            Some(poll) => poll,
+            None => return Err("The poll is still loading".into()),
+        };
+        let current = self.state.votes.get(&id).cloned().unwrap_or(0);
+        if current < 0 {
+            return Err("Take your dots against this choice off first".into());
+        }
+        if poll.poll.voting_method == api::VotingMethod::Quadratic {
+            let cost = self.dot_cost(current + 1);
+            let remaining = self.choice_remaining(id);
+            if cost > remaining {
+                return Err(format!(
+                    "Another dot costs {} credits, you have {} left",
+                    cost, remaining
+                ));
+            }
+            return Ok(());
+        }
+        if let Some(cap) = poll.poll.dot_cap() {
+            if current >= cap {
+                return Err(if cap == 1 {
+                    "Only one dot per choice".into()
+                } else {
+                    format!("At most {} dots per choice", cap)
+                });
+            }
+        }
+
+        // Make sure enough dots are left over to reach the minimum spread
+        let spread = self.choices_voted() + if current == 0 { 1 } else { 0 };
+        let needed = (poll.min_choices() - spread).max(0);
+        if self.total_remaining(poll) - 1 < needed {
+            return Err(format!(
+                "Spread your dots: use at least {} different choices",
+                poll.min_choices()
+            ));
+        }
+        Ok(())
+    }
+
+    /// What the `dots`th dot on a choice costs from its budget, which holds credits on
+    /// quadratic polls.
+    fn dot_cost(&self, dots: i32) -> i32 {
+        match &self.state.poll {
+            Some(poll) if poll.poll.voting_method == api::VotingMethod::Quadratic => {
+                api::quadratic_cost(dots) - api::quadratic_cost(dots - 1)
+            }
+            _ => 1,
+        }
+    }
+
+    /// Turns the ranking into votes, 1 for first place and so on.
+    fn rank_votes(&mut self) {
+        self.state.votes = self
+            .state
+            .ranking
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, i as i32 + 1))
+            .collect();
+    }
+
+    /// Whether the ballot is filled in enough to submit under the poll's voting method.
+    fn ballot_complete(&self, poll: &api::Poll) -> bool {
+        match poll.poll.voting_method {
+            api::VotingMethod::Dots => {
+                self.total_remaining(poll) == 0 && self.choices_voted() >= poll.min_choices()
+            }
+            api::VotingMethod::Approval
+            | api::VotingMethod::Score
+            | api::VotingMethod::Quadratic => self.choices_voted() > 0,
+            api::VotingMethod::RankedChoice | api::VotingMethod::Borda => {
+                !self.state.ranking.is_empty()
+            }
+        }
+    }
+
+    fn choices_voted(&self) -> i32 {
+        self.state.votes.values().filter(|dots| **dots > 0).count() as i32
+    }
+
+    fn instructions(&self, poll: &api::Poll) -> String {
+        match poll.poll.voting_method {
+            api::VotingMethod::Dots => {}
+            api::VotingMethod::Quadratic => {
+                return "Click on a choice to buy dots with your credits. Each extra dot on the same choice costs more: n dots cost n\u{b2} credits.".into();
+            }
+            api::VotingMethod::Approval => {
+                return "Tick every choice you'd be happy with.".into();
+            }
+            api::VotingMethod::Score => {
+                return format!(
+                    "Score each choice from 0 to {}. The highest total wins.",
+                    api::MAX_SCORE
+                );
+            }
+            api::VotingMethod::RankedChoice | api::VotingMethod::Borda => {
+                return "Drag the choices into your order of preference, favourite first.".into();
+            }
+        }
+        let mut text =
+            "Click on a choice to allocate dots. You must allocate all dots to vote.".to_owned();
+        match poll.poll.dot_cap() {
+            Some(1) => text.push_str(" Only one dot per choice."),
+            Some(cap) => text.push_str(&format!(" At most {} dots per choice.", cap)),
+            None => {}
+        }
+        if poll.budgets().len() > 1 {
+            text.push_str(" Some categories have dots of their own to allocate.");
+        }
+        if poll.min_choices() > 1 {
+            text.push_str(&format!(
+                " Spread your dots over at least {} choices.",
+                poll.min_choices()
+            ));
+        }
+        if let Some(against) = poll.poll.negative_dots() {
+            text.push_str(&format!(
+                " You may also place up to {} dots against choices you oppose.",
+                against
+            ));
+        }
+        text
+    }
+
     fn show_can_vote(&self, poll: &api::Poll) -> Html {
-        let can_submit = !self.state.name.is_empty() && self.state.dots_remaining == 0;
+        let anonymous = poll.poll.anonymous;
+        let invited = self.can_vote_invited(poll);
+        let can_submit = (anonymous || !self.state.name.is_empty())
+            && invited
+            && self.ballot_complete(poll)
+            && !self.state.submitting;
+        let dots_left = match poll.poll.voting_method {
+            api::VotingMethod::Dots => html!(
+                <>
+                    <div class="level-item">{format!("Dots Left: {}", self.total_remaining(poll))}</div>
+                    { if poll.poll.negative_dots().is_some() {
+                        html!(<div class="level-item">{format!("Against Left: {}", self.state.against_remaining)}</div>)
+                    } else {
+                        html!()
+                    } }
+                </>
+            ),
+            api::VotingMethod::Quadratic => {
+                html!(<div class="level-item">{format!("Credits Left: {}", self.total_remaining(poll))}</div>)
+            }
+            _ => html!(),
+        };
+        let changing = self.state.edit_token.is_some();
+        let submit_class = if self.state.submitting {
+            "button is-primary is-fullwidth is-loading"
+        } else {
+            "button is-primary is-fullwidth"
+        };
         html!(
             <Panel>
                 <PanelHeading>
@@ -205,32 +955,94 @@ impl ShowPoll {
                             </div>
                         </div>
                         <div class="level-right">
-                            <div class="level-item">
-                                {format!("Dots Left: {}", self.state.dots_remaining)}
-                            </div>
+                            { self.countdown(poll) }
+                            { dots_left }
                         </div>
                     </div>
                 </PanelHeading>
                 <PanelBlock class="notification is-light mb-0">
-                    <p class="has-text-centered">{"Click on a choice to allocate dots. You must allocate
-                        all dots to vote."}</p>
-                </PanelBlock>
-                { for poll.choices.iter().map(|choice| self.vote_choice(choice)) }
-                <PanelBlock>
-                    <input class="input is-fullwidth" type="text" placeholder="Your Name..."
-                        value=&self.state.name oninput=self.link.callback(|e: InputData|
-                        Msg::UpdateName(e.value)) />
+                    <p class="has-text-centered">{self.instructions(poll)}</p>
                 </PanelBlock>
+                { self.ballot(poll) }
+                { if !invited {
+                    self.view_uninvited()
+                } else if anonymous { html!(
+                    <PanelBlock>
+                        <span class="panel-icon"><i class="fas fa-user-secret" aria-hidden="true"></i></span>
+                        <span class="has-text-grey">{"This poll is anonymous: your name isn't asked for, and only totals are shown."}</span>
+                    </PanelBlock>
+                ) } else if poll.poll.invite_only { html!(
+                    <PanelBlock>
+                        <div class="control has-icons-left is-expanded">
+                            <input class="input is-fullwidth" type="text" value=&self.state.name readonly=true />
+                            <span class="icon is-left"><i class="fas fa-lock" aria-hidden="true"></i></span>
+                        </div>
+                    </PanelBlock>
+                ) } else { html!(
+                    <PanelBlock>
+                        <input class="input is-fullwidth" type="text" placeholder="Your Name..."
+                            value=&self.state.name oninput=self.link.callback(|e: InputData|
+                            Msg::UpdateName(e.value)) />
+                    </PanelBlock>
+                ) } }
+                { if let Some(error) = &self.state.vote_error {
+                    html!(<ErrorBlock error=error.clone() onretry=self.link.callback(|_| Msg::SubmitVote) />)
+                } else {
+                    html!()
+                } }
                 <PanelBlock>
-                    <button class="button is-primary is-fullwidth" disabled={!can_submit} onclick=self.link.callback(|_| Msg::SubmitVote)>
-                        {"Submit Votes"}
+                    <button class=submit_class disabled={!can_submit} onclick=self.link.callback(|_| Msg::SubmitVote)>
+                        { if changing { "Update Vote" } else { "Submit Votes" } }
                     </button>
                 </PanelBlock>
+                { if changing { html!(
+                    <PanelBlock>
+                        <button class="button is-light is-fullwidth" disabled=self.state.submitting
+                            onclick=self.link.callback(|_| Msg::CancelChange)>
+                            {"Keep my vote as it was"}
+                        </button>
+                    </PanelBlock>
+                ) } else { html!() } }
+                { self.show_creator_actions(poll) }
             </Panel>
         )
     }
 
+    /// Whether this browser may vote, which on invite only polls takes an invite that hasn't
+    /// been used, or the ballot it was used for.
+    fn can_vote_invited(&self, poll: &api::Poll) -> bool {
+        !poll.poll.invite_only
+            || self.state.edit_token.is_some()
+            || self
+                .state
+                .invitee
+                .as_ref()
+                .is_some_and(|invitee| !invitee.voted)
+    }
+
+    /// Why this browser can't vote on an invite only poll.
+    fn view_uninvited(&self) -> Html {
+        if let Some(error) = &self.state.invite_error {
+            return html!(<ErrorBlock error=error.clone() />);
+        }
+        let message = match &self.state.invitee {
+            Some(invitee) => format!(
+                "{} has already voted with this invite, from another browser.",
+                invitee.name
+            ),
+            None if self.state.invite.is_some() => "Checking your invite...".to_owned(),
+            None => "Only the people invited can vote on this poll. Open the link you were sent to vote.".to_owned(),
+        };
+        html!(
+            <PanelBlock>
+                <span class="panel-icon"><i class="fas fa-envelope" aria-hidden="true"></i></span>
+                <span class="has-text-grey">{message}</span>
+            </PanelBlock>
+        )
+    }
+
     fn show_voted(&self, poll: &api::Poll) -> Html {
+        let visible = self.results_visible(poll);
         html!(
             <Panel>
                 <PanelHeading>
@@ -240,25 +1052,552 @@ impl ShowPoll {
                                 {&poll.poll.title}
                             </div>
                         </div>
+                        <div class="level-right">
+                            { self.countdown(poll) }
+                        </div>
                     </div>
                 </PanelHeading>
                 <PanelBlock class="notification is-success is-light mb-0">
-                    <p class="has-text-centered">{"Vote sent! Click below to see results."}</p>
+                    <p class="has-text-centered">{ if visible {
+                        "Vote sent! Click below to see results.".to_owned()
+                    } else if !poll.poll.results_revealed() {
+                        "Vote sent! The results will be revealed once voting ends.".to_owned()
+                    } else {
+                        format!("Vote sent! {}.", capitalise(poll.poll.results_hidden_reason()))
+                    } }</p>
+                </PanelBlock>
+                { self.ballot(poll) }
+                { if let Some(error) = &self.state.vote_error {
+                    html!(<ErrorBlock error=error.clone() onretry=self.link.callback(|_| Msg::WithdrawVote) />)
+                } else {
+                    html!()
+                } }
+                { if visible { html!(
+                    <PanelBlock>
+                        <RouterButton<crate::MountedRoute> route={crate::MountedRoute(crate::AppRoute::PollResults(self.props.poll_id.clone()))} classes="button is-primary is-fullwidth">
+                            {"View Results"}
+                        </RouterButton<crate::MountedRoute>>
+                    </PanelBlock>
+                ) } else { html!() } }
+                { if self.state.edit_token.is_some() { self.show_vote_actions() } else { html!() } }
+                { self.show_creator_actions(poll) }
+            </Panel>
+        )
+    }
+
+    /// The poll before it opens or after it closes, with the ballot from here if there is one.
+    fn show_locked(&self, poll: &api::Poll, status: api::PollStatus) -> Html {
+        let message = match (status, poll.poll.opens_at, poll.poll.closes_at) {
+            _ if poll.poll.phase == Some(api::PollPhase::Reveal) => {
+                "Voting has ended and the results are in.".into()
+            }
+            (api::PollStatus::Scheduled, Some(opens_at), _) => format!(
+                "Voting opens in {}, on {}.",
+                time::countdown(self.state.now, opens_at),
+                time::format(opens_at)
+            ),
+            (_, _, Some(closes_at)) => format!("Voting closed on {}.", time::format(closes_at)),
+            _ => "Voting has closed.".into(),
+        };
+        let closed = status == api::PollStatus::Closed;
+        html!(
+            <Panel>
+                <PanelHeading>
+                    <div class="level">
+                        <div class="level-left">
+                            <div class="level-item">
+                                {&poll.poll.title}
+                            </div>
+                        </div>
+                    </div>
+                </PanelHeading>
+                <PanelBlock class="notification is-warning is-light mb-0">
+                    <p class="has-text-centered">{message}</p>
+                </PanelBlock>
+                { self.ballot(poll) }
+                { if let Some(error) = &self.state.vote_error {
+                    html!(<ErrorBlock error=error.clone() />)
+                } else {
+                    html!()
+                } }
+                { if (closed || self.state.voted) && self.results_visible(poll) { html!(
+                    <PanelBlock>
+                        <RouterButton<crate::MountedRoute> route={crate::MountedRoute(crate::AppRoute::PollResults(self.props.poll_id.clone()))} classes="button is-primary is-fullwidth">
+                            {"View Results"}
+                        </RouterButton<crate::MountedRoute>>
+                    </PanelBlock>
+                ) } else { html!() } }
+                { self.show_creator_actions(poll) }
+            </Panel>
+        )
+    }
+
+    /// Whether this browser may see the results, going by the tokens it holds.
+    fn results_visible(&self, poll: &api::Poll) -> bool {
+        let viewer = if self.state.admin_token.is_some() {
+            api::ResultsViewer::Creator
+        } else if self.state.edit_token.is_some() {
+            api::ResultsViewer::Voter
+        } else {
+            api::ResultsViewer::Anyone
+        };
+        poll.poll.results_visible_to(viewer, self.state.now)
+    }
+
+    /// A brainstorming poll collecting choices, which anyone can add to. The facilitator can
+    /// merge duplicates and start the vote from here.
+    fn show_collect(&self, poll: &api::Poll) -> Html {
+        let facilitating = self.state.admin_token.is_some();
+        let add_class = if self.state.adding {
+            "button is-primary is-loading"
+        } else {
+            "button is-primary"
+        };
+        let problems = match &self.state.add_error {
+            Some(api::ApiError::Validation { fields, .. }) => fields.get("details"),
+            _ => None,
+        };
+        html!(
+            <Panel>
+                <PanelHeading>
+                    <div class="level">
+                        <div class="level-left">
+                            <div class="level-item">
+                                {&poll.poll.title}
+                            </div>
+                        </div>
+                        <div class="level-right">
+                            <div class="level-item">
+                                <span class="tag is-info is-light">{"Collecting choices"}</span>
+                            </div>
+                        </div>
+                    </div>
+                </PanelHeading>
+                <PanelBlock class="notification is-info is-light mb-0">
+                    <p class="has-text-centered">{"Add your ideas below. Voting starts once the facilitator is ready."}</p>
                 </PanelBlock>
-                { for poll.choices.iter().map(|choice| self.vote_choice(choice)) }
+                { if poll.choices.is_empty() {
+                    html!(<PanelBlock><p class="has-text-grey">{"No choices yet, be the first!"}</p></PanelBlock>)
+                } else {
+                    let grouped = !poll.poll.categories.is_empty();
+                    html!({ for poll.poll.group_choices(&poll.choices).into_iter().map(|(category, choices)| html!(
+                        <>
+                            { if grouped { self.category_heading(poll, category) } else { html!() } }
+                            { for choices.into_iter().map(|choice| self.view_collected(poll, choice, facilitating)) }
+                        </>
+                    )) })
+                } }
                 <PanelBlock>
-                    <RouterButton<crate::AppRoute> route={crate::AppRoute::PollResults(self.props.poll_id.clone())} classes="button is-primary is-fullwidth">
-                        {"View Results"}
-                    </RouterButton<crate::AppRoute>>
+                    <form class="control" onsubmit=self.link.callback(|e: FocusEvent| {
+                        e.prevent_default();
+                        Msg::AddChoice
+                    })>
+                        <div class="field has-addons">
+                            <p class="control is-expanded">
+                                <input class=if problems.is_some() { "input is-danger" } else { "input" } type="text"
+                                    placeholder="Add a choice..." value=&self.state.new_choice
+                                    oninput=self.link.callback(|e: InputData| Msg::UpdateNewChoice(e.value)) />
+                            </p>
+                            { self.new_category_select(poll) }
+                            <p class="control">
+                                <button class=add_class type="submit"
+                                    disabled={self.state.new_choice.trim().is_empty() || self.state.adding}>
+                                    {"Add"}
+                                </button>
+                            </p>
+                        </div>
+                        { match problems {
+                            Some(problems) => html!(<p class="help is-danger">{problems.join(", ")}</p>),
+                            None => html!(),
+                        } }
+                    </form>
                 </PanelBlock>
+                { match &self.state.add_error {
+                    Some(error) if problems.is_none() => html!(<ErrorBlock error=error.clone() onretry=self.link.callback(|_| Msg::AddChoice) />),
+                    _ => html!(),
+                } }
+                { if facilitating { self.show_facilitator_actions(poll) } else { html!() } }
+                { self.show_creator_actions(poll) }
             </Panel>
         )
     }
 
+    /// Picks the category for a new choice, on polls that have them.
+    fn new_category_select(&self, poll: &api::Poll) -> Html {
+        if poll.poll.categories.is_empty() {
+            return html!();
+        }
+        html!(
+            <p class="control">
+                <span class="select">
+                    <select onchange=self.link.callback(|e: ChangeData| match e {
+                        ChangeData::Select(select) => Msg::UpdateNewCategory(
+                            usize::try_from(select.selected_index() - 1).ok(),
+                        ),
+                        _ => Msg::UpdateNewCategory(None),
+                    })>
+                        <option selected={self.state.new_category.is_none()}>{"No category"}</option>
+                        { for poll.poll.categories.iter().enumerate().map(|(i, category)| html!(
+                            <option selected={self.state.new_category == Some(i)}>{&category.name}</option>
+                        )) }
+                    </select>
+                </span>
+            </p>
+        )
+    }
+
+    fn view_collected(
+        &self,
+        poll: &api::Poll,
+        choice: &api::PollChoice,
+        facilitating: bool,
+    ) -> Html {
+        let id = choice.id;
+        match self.merged_into(poll, id) {
+            Some(into) => html!(
+                <PanelBlock>
+                    <span class="has-text-grey">
+                        <s>{&choice.details}</s>{format!(" (merged into \"{}\")", into.details)}
+                    </span>
+                </PanelBlock>
+            ),
+            None if facilitating => html!(
+                <PanelBlock>
+                    <label class="checkbox">
+                        <input type="checkbox" class="mr-2" checked=self.state.merging.contains(&id)
+                            onclick=self.link.callback(move |_| Msg::ToggleMerge(id)) />
+                        {&choice.details}
+                    </label>
+                </PanelBlock>
+            ),
+            None => html!(<PanelBlock>{&choice.details}</PanelBlock>),
+        }
+    }
+
+    /// Merging duplicates and starting the vote, for whoever created a brainstorming poll.
+    fn show_facilitator_actions(&self, poll: &api::Poll) -> Html {
+        let merged: usize = self.state.merges.iter().map(|merge| merge.from.len()).sum();
+        let start_class = if self.state.admin_busy {
+            "button is-primary is-loading"
+        } else {
+            "button is-primary"
+        };
+        html!(
+            <>
+                { if let Some(error) = &self.state.admin_error {
+                    html!(<ErrorBlock error=error.clone() onretry=self.link.callback(|_| Msg::AdvancePhase(api::PollPhase::Vote)) />)
+                } else {
+                    html!()
+                } }
+                <PanelBlock>
+                    <div class="buttons is-centered" style="width:100%;">
+                        <button class="button is-light" disabled={self.state.merging.len() < 2}
+                            title="Tick choices that say the same thing to keep only the first"
+                            onclick=self.link.callback(|_| Msg::MergeSelected)>
+                            <span class="icon"><i class="fas fa-compress-alt"></i></span>
+                            <span>{"Merge selected"}</span>
+                        </button>
+                        { if self.state.merges.is_empty() {
+                            html!()
+                        } else {
+                            html!(
+                                <button class="button is-light" onclick=self.link.callback(|_| Msg::UndoMerges)>
+                                    {"Undo merges"}
+                                </button>
+                            )
+                        } }
+                        <button class=start_class disabled={self.state.admin_busy || poll.choices.len().saturating_sub(merged) < 2}
+                            onclick=self.link.callback(|_| Msg::AdvancePhase(api::PollPhase::Vote))>
+                            <span class="icon"><i class="fas fa-vote-yea"></i></span>
+                            <span>{"Start voting"}</span>
+                        </button>
+                    </div>
+                </PanelBlock>
+            </>
+        )
+    }
+
+    /// How long until the poll closes, while it's open and has a closing time.
+    fn countdown(&self, poll: &api::Poll) -> Html {
+        match poll.poll.closes_at {
+            Some(closes_at) if poll.poll.status(self.state.now) == api::PollStatus::Open => html!(
+                <div class="level-item">
+                    <span class="tag is-warning is-light" title=time::format(closes_at)>
+                        {format!("Closes in {}", time::countdown(self.state.now, closes_at))}
+                    </span>
+                </div>
+            ),
+            _ => html!(),
+        }
+    }
+
+    /// Lets whoever created the poll from this browser end voting early, or reveal the results
+    /// of a brainstorming poll.
+    fn show_creator_actions(&self, poll: &api::Poll) -> Html {
+        let token = match &self.state.admin_token {
+            Some(token) => token,
+            None => return html!(),
+        };
+        let manage = crate::AppRoute::PollAdmin(self.props.poll_id.clone(), token.to_string());
+        let closed = poll.poll.status(self.state.now) == api::PollStatus::Closed;
+        // Collecting polls have their own actions, see `show_facilitator_actions`
+        let acting = !closed && !poll.poll.collecting();
+        // Brainstorming polls end voting by revealing their results
+        let reveal = poll.poll.phase == Some(api::PollPhase::Vote);
+        let (label, icon) = if reveal {
+            ("Reveal results", "fas fa-eye")
+        } else {
+            ("Close voting now", "fas fa-lock")
+        };
+        let act = move |_| {
+            if reveal {
+                Msg::AdvancePhase(api::PollPhase::Reveal)
+            } else {
+                Msg::ClosePoll
+            }
+        };
+        let class = if self.state.admin_busy {
+            "button is-danger is-outlined is-loading"
+        } else {
+            "button is-danger is-outlined"
+        };
+        html!(
+            <>
+                { match &self.state.admin_error {
+                    Some(error) if acting => html!(<ErrorBlock error=error.clone() onretry=self.link.callback(act) />),
+                    _ => html!(),
+                } }
+                <PanelBlock>
+                    <div class="buttons is-centered" style="width:100%;">
+                        { if acting {
+                            html!(
+                                <button class=class disabled=self.state.admin_busy onclick=self.link.callback(act)>
+                                    <span class="icon"><i class=icon></i></span>
+                                    <span>{label}</span>
+                                </button>
+                            )
+                        } else {
+                            html!()
+                        } }
+                        <RouterButton<crate::MountedRoute> route={crate::MountedRoute(manage)} classes="button is-light">
+                            <span class="icon"><i class="fas fa-cog"></i></span>
+                            <span>{"Manage poll"}</span>
+                        </RouterButton<crate::MountedRoute>>
+                    </div>
+                </PanelBlock>
+            </>
+        )
+    }
+
+    /// Buttons to change or withdraw the ballot cast from here.
+    fn show_vote_actions(&self) -> Html {
+        let withdraw_class = if self.state.submitting {
+            "button is-danger is-light is-loading"
+        } else {
+            "button is-danger is-light"
+        };
+        html!(
+            <PanelBlock>
+                <div class="buttons is-centered" style="width:100%;">
+                    <button class="button is-light" disabled=self.state.submitting
+                        onclick=self.link.callback(|_| Msg::ChangeVote)>
+                        <span class="icon"><i class="fas fa-edit"></i></span>
+                        <span>{"Change my vote"}</span>
+                    </button>
+                    <button class=withdraw_class disabled=self.state.submitting
+                        onclick=self.link.callback(|_| Msg::WithdrawVote)>
+                        <span class="icon"><i class="fas fa-undo"></i></span>
+                        <span>{"Withdraw vote"}</span>
+                    </button>
+                </div>
+            </PanelBlock>
+        )
+    }
+
+    /// The choices to vote on, grouped under their categories except on ranked polls, which
+    /// rank every choice in one list.
+    fn ballot(&self, poll: &api::Poll) -> Html {
+        if poll.poll.voting_method.is_ranked() {
+            return self
+                .state
+                .ranking
+                .iter()
+                .enumerate()
+                .filter_map(|(i, id)| {
+                    let choice = poll.choices.iter().find(|choice| choice.id == *id)?;
+                    Some(self.rank_choice(i, choice))
+                })
+                .collect();
+        }
+        let grouped = !poll.poll.categories.is_empty();
+        poll.poll
+            .group_choices(&poll.choices)
+            .into_iter()
+            .map(|(category, choices)| {
+                html!(
+                    <>
+                        { if grouped { self.category_heading(poll, category) } else { html!() } }
+                        { for choices.into_iter().map(|choice| match poll.poll.voting_method {
+                            api::VotingMethod::Approval => self.approve_choice(choice),
+                            api::VotingMethod::Score => self.score_choice(choice),
+                            _ => self.vote_choice(choice),
+                        }) }
+                    </>
+                )
+            })
+            .collect()
+    }
+
+    /// Heads a category's choices, with the dots left when it has its own.
+    fn category_heading(&self, poll: &api::Poll, category: Option<usize>) -> Html {
+        let name = category
+            .and_then(|category| poll.poll.categories.get(category))
+            .map(|category| category.name.as_str())
+            .unwrap_or("Other");
+        let budgeted = category.is_some() && poll.budgets().contains(&category);
+        let unit = if poll.poll.voting_method == api::VotingMethod::Quadratic {
+            "credits"
+        } else {
+            "dots"
+        };
+        html!(
+            <PanelBlock class="has-background-light">
+                <strong class="mr-2">{name}</strong>
+                { if budgeted && !self.locked() {
+                    html!(<span class="tag is-info is-light">{format!("{} {} left", self.dots_remaining(poll, category), unit)}</span>)
+                } else {
+                    html!()
+                } }
+            </PanelBlock>
+        )
+    }
+
+    /// Marks which category a choice is in, where choices aren't grouped by them.
+    fn category_tag(&self, choice: &api::PollChoice) -> Html {
+        let category = self.state.poll.as_ref().and_then(|poll| {
+            poll.poll
+                .categories
+                .get(choice.category?)
+                .map(|category| category.name.clone())
+        });
+        match category {
+            Some(name) => html!(<span class="tag is-light ml-2">{name}</span>),
+            None => html!(),
+        }
+    }
+
+    fn approve_choice(&self, choice: &api::PollChoice) -> Html {
+        let id = choice.id;
+        let approved = self.state.votes.get(&id).cloned().unwrap_or(0) > 0;
+        html!(
+            <PanelBlock tag="label" style="display:block;">
+                <input type="checkbox" class="mr-2" checked=approved disabled=self.locked()
+                    onclick=self.link.callback(move |_| Msg::ToggleApproval(id)) />
+                {&choice.details}
+            </PanelBlock>
+        )
+    }
+
+    fn score_choice(&self, choice: &api::PollChoice) -> Html {
+        let id = choice.id;
+        let score = self.state.votes.get(&id).cloned().unwrap_or(0);
+        html!(
+            <PanelBlock style="display:block;">
+              <div class="level">
+                <div class="level-left">
+                    <div class="level-item">
+                        <span class="panel-icon">
+                            <i class="fas fa-angle-right" aria-hidden="true"></i>
+                        </span>
+                        {&choice.details}
+                    </div>
+                </div>
+                <div class="level-right">
+                    <div class="level-item">
+                        <input type="range" min="0" max=api::MAX_SCORE step="1" value=score
+                            disabled=self.locked()
+                            oninput=self.link.callback(move |e: InputData| Msg::SetScore(id, e.value.parse().unwrap_or(0))) />
+                        <span class="tag is-info is-light ml-2">{score}</span>
+                    </div>
+                </div>
+              </div>
+            </PanelBlock>
+        )
+    }
+
+    fn rank_choice(&self, i: usize, choice: &api::PollChoice) -> Html {
+        let last = self.state.ranking.len() - 1;
+        let draggable = if self.locked() { "false" } else { "true" };
+        let arrows = if self.locked() {
+            html!()
+        } else {
+            html!(
+                <div class="buttons has-addons are-small">
+                    <button class="button" disabled={i == 0}
+                        onclick=self.link.callback(move |_| Msg::MoveRank(i, i.saturating_sub(1)))>
+                        <span class="icon"><i class="fas fa-arrow-up"></i></span>
+                    </button>
+                    <button class="button" disabled={i == last}
+                        onclick=self.link.callback(move |_| Msg::MoveRank(i, i + 1))>
+                        <span class="icon"><i class="fas fa-arrow-down"></i></span>
+                    </button>
+                </div>
+            )
+        };
+        html!(
+            <div class="panel-block" style="display:block;" draggable=draggable
+                ondragstart=self.link.callback(move |e: DragEvent| {
+                    // Firefox won't start a drag without some data
+                    if let Some(data) = e.data_transfer() {
+                        let _ = data.set_data("text/plain", "");
+                    }
+                    Msg::DragStart(i)
+                })
+                ondragover=self.link.batch_callback(|e: DragEvent| { e.prevent_default(); vec![] })
+                ondrop=self.link.callback(move |e: DragEvent| { e.prevent_default(); Msg::DropAt(i) })>
+              <div class="level">
+                <div class="level-left">
+                    <div class="level-item">
+                        <span class="tag is-info is-light mr-2">{i + 1}</span>
+                        { if self.locked() { html!() } else { html!(
+                            <span class="panel-icon"><i class="fas fa-grip-vertical" aria-hidden="true"></i></span>
+                        ) } }
+                        {&choice.details}
+                        { self.category_tag(choice) }
+                    </div>
+                </div>
+                <div class="level-right">
+                    <div class="level-item">{ arrows }</div>
+                </div>
+              </div>
+            </div>
+        )
+    }
+
     fn vote_choice(&self, choice: &api::PollChoice) -> Html {
         let votes = self.state.votes.get(&choice.id).cloned().unwrap_or(0);
         let id = choice.id;
-        let block_tag = if self.state.voted { "div" } else { "a" };
+        let block_tag = if self.locked() { "div" } else { "a" };
+        let at_cap = match self.state.poll.as_ref().and_then(|p| p.poll.dot_cap()) {
+            Some(cap) => votes >= cap && !self.locked(),
+            None => false,
+        };
+        let quadratic = self
+            .state
+            .poll
+            .as_ref()
+            .map(|p| p.poll.voting_method == api::VotingMethod::Quadratic)
+            .unwrap_or(false);
+        let can_oppose = !self.locked()
+            && self
+                .state
+                .poll
+                .as_ref()
+                .is_some_and(|p| p.poll.negative_dots().is_some());
+        let rejected = match &self.state.rejected {
+            Some((rejected_id, reason)) if *rejected_id == id => Some(reason),
+            _ => None,
+        };
         html!(
             <PanelBlock tag={block_tag} style="display:block;" onclick=self.link.callback(move |_| Msg::AddDot(id))>
               <div class="level">
@@ -272,11 +1611,26 @@ impl ShowPoll {
                 </div>
                 <div class="level-right">
                     <div class="level-item">
-                        { for (0..votes).map(|_| html!(<span class="icon has-text-info"><i class="fas fa-circle"></i></span>)) }
-                        { if votes > 0 && !self.state.voted { html!(<span class="icon"><div class="delete" onclick=self.link.callback(move |e: MouseEvent| { e.stop_propagation(); Msg::RemoveDot(id) })></div></span>) } else { html!()} }
+                        { if at_cap { html!(<span class="tag is-light mr-2">{"Max reached"}</span>) } else { html!() } }
+                        { if quadratic && !self.locked() {
+                            html!(<span class="tag is-light mr-2">{format!("Next: {} credits", self.dot_cost(votes + 1))}</span>)
+                        } else {
+                            html!()
+                        } }
+                        { if can_oppose { html!(
+                            <button class="button is-small is-danger is-light mr-2" title="Place a dot against this choice"
+                                disabled={votes > 0 || self.state.against_remaining == 0}
+                                onclick=self.link.callback(move |e: MouseEvent| { e.stop_propagation(); Msg::AddDotAgainst(id) })>
+                                <span class="icon"><i class="fas fa-thumbs-down"></i></span>
+                            </button>
+                        ) } else { html!() } }
+                        { draw_dots(votes.max(0), "icon has-text-info", "", "fas fa-circle") }
+                        { draw_dots(-votes.min(0), "icon has-text-danger", "", "fas fa-minus-circle") }
+                        { if votes != 0 && !self.locked() { html!(<span class="icon"><div class="delete" onclick=self.link.callback(move |e: MouseEvent| { e.stop_propagation(); Msg::RemoveDot(id) })></div></span>) } else { html!()} }
                     </div>
                 </div>
               </div>
+              { if let Some(reason) = rejected { html!(<p class="help is-danger">{reason}</p>) } else { html!() } }
             </PanelBlock>
         )
     }
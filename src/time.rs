@@ -0,0 +1,68 @@
+//! Times as the browser shows them, in the visitor's own time zone.
+use js_sys::Date;
+use wasm_bindgen::JsValue;
+
+use crate::api::Timestamp;
+
+pub fn now() -> Timestamp {
+    #[cfg(target_arch = "wasm32")]
+    let millis = Date::now() as i64;
+    // Outside the browser there's no `Date`, which only the mock backend's tests run into
+    #[cfg(not(target_arch = "wasm32"))]
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as i64);
+    Timestamp::from_millis(millis).expect("the browser clock is out of range")
+}
+
+fn date(time: Timestamp) -> Date {
+    Date::new(&JsValue::from_f64(time.millis() as f64))
+}
+
+/// Reads the value of a `datetime-local` input, which is local time. Blank or unparseable
+/// values are `None`.
+pub fn parse_input(value: &str) -> Option<Timestamp> {
+    let millis = Date::parse(value);
+    if value.trim().is_empty() || millis.is_nan() {
+        return None;
+    }
+    Timestamp::from_millis(millis as i64)
+}
+
+/// The value a `datetime-local` input needs to show `time`.
+pub fn input_value(time: Timestamp) -> String {
+    let date = date(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}",
+        date.get_full_year(),
+        date.get_month() + 1,
+        date.get_date(),
+        date.get_hours(),
+        date.get_minutes()
+    )
+}
+
+/// `time` the way the visitor's locale writes dates and times.
+pub fn format(time: Timestamp) -> String {
+    date(time)
+        .to_locale_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+/// How long from `now` until `then`, to the nearest second, like `2d 4h` or `3m 20s`.
+pub fn countdown(now: Timestamp, then: Timestamp) -> String {
+    let seconds = ((then.millis() - now.millis()) / 1000).max(0);
+    let (days, hours, minutes, seconds) = (
+        seconds / 86_400,
+        seconds / 3_600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+    );
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m {}s", minutes, seconds)
+    }
+}
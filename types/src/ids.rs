@@ -0,0 +1,108 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+macro_rules! numeric_id {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(
+            Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub i32);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl From<i32> for $name {
+            fn from(id: i32) -> Self {
+                $name(id)
+            }
+        }
+    };
+}
+
+numeric_id!(
+    /// A poll's database id. Polls are addressed by their `PollUuid` in URLs.
+    PollId
+);
+numeric_id!(
+    /// Identifies a choice, unique across all polls.
+    ChoiceId
+);
+numeric_id!(
+    /// Identifies a single vote row, i.e. one voter's dots on one choice.
+    VoteId
+);
+numeric_id!(
+    /// Identifies a ballot, the vote rows cast together. Two voters giving the same name still
+    /// have different ballots.
+    BallotId
+);
+
+/// The public identifier of a poll, as used in URLs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct PollUuid(pub String);
+
+impl PollUuid {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PollUuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for PollUuid {
+    fn from(uuid: String) -> Self {
+        PollUuid(uuid)
+    }
+}
+
+impl From<&str> for PollUuid {
+    fn from(uuid: &str) -> Self {
+        PollUuid(uuid.to_owned())
+    }
+}
+
+macro_rules! secret_token {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[serde(transparent)]
+        pub struct $name(pub String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+secret_token!(
+    /// The secret handed back to whoever cast a ballot, which lets them change or withdraw it.
+    EditToken
+);
+secret_token!(
+    /// The secret handed back to whoever created a poll, which lets them manage it.
+    AdminToken
+);
+secret_token!(
+    /// The secret in an invitee's link, which lets them vote as themselves on a poll with a
+    /// roster.
+    InviteToken
+);
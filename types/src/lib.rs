@@ -0,0 +1,13 @@
+//! The dotdotvote API's wire format, shared by the dotdotyew frontend, the server and anything
+//! else that speaks to either, along with how votes in it are counted.
+//!
+//! Each API version gets its own module. Additive changes (new optional fields with serde
+//! defaults) can land in the current version, anything that would break an existing client
+//! goes in a new one.
+mod ids;
+pub mod tally;
+mod timestamp;
+pub mod v1;
+
+pub use ids::{AdminToken, BallotId, ChoiceId, EditToken, InviteToken, PollId, PollUuid, VoteId};
+pub use timestamp::Timestamp;
@@ -0,0 +1,539 @@
+//! Counting votes under each `VotingMethod`. Pure functions of the results, so the frontend
+//! and the server always agree on who won.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::v1::{quadratic_cost, PollResults, Vote, VotingMethod};
+use crate::{BallotId, ChoiceId};
+
+/// How one choice fared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChoiceTally {
+    pub choice_id: ChoiceId,
+    /// Dots, approvals, total score, Borda points or quadratic votes. For instant runoff, the
+    /// votes the choice held in the last round it took part in.
+    pub total: i32,
+    /// Credits voters spent on the choice, only non-zero on quadratic polls.
+    pub credits: i32,
+    /// What went into `total`, for polls that allow dots against choices: the points for it,
+    /// and the points against it as a positive number.
+    pub positive: i32,
+    pub negative: i32,
+    /// 1 for the winner. Tied choices all get the best rank between them.
+    pub rank: usize,
+    pub tied: bool,
+}
+
+/// One round of an instant runoff count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Round {
+    /// Votes for every choice still in the running, in poll order.
+    pub counts: Vec<(ChoiceId, i32)>,
+    /// Ballots that ranked no choice still in the running.
+    pub exhausted: i32,
+    /// The choices knocked out at the end of this round, empty for the final round.
+    pub eliminated: Vec<ChoiceId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tally {
+    /// Every choice, in poll order.
+    pub choices: Vec<ChoiceTally>,
+    /// Instant runoff rounds, empty for other voting methods.
+    pub rounds: Vec<Round>,
+}
+
+impl Tally {
+    pub fn get(&self, choice_id: ChoiceId) -> Option<&ChoiceTally> {
+        self.choices
+            .iter()
+            .find(|choice| choice.choice_id == choice_id)
+    }
+
+    /// The choices ranked first. More than one means a tie, none means nothing was voted on.
+    pub fn winners(&self) -> Vec<ChoiceId> {
+        let all: Vec<ChoiceId> = self.choices.iter().map(|choice| choice.choice_id).collect();
+        self.winners_among(&all)
+    }
+
+    /// The best ranked of `choices`, for the winners of one category. Empty if none of them
+    /// got any votes.
+    pub fn winners_among(&self, choices: &[ChoiceId]) -> Vec<ChoiceId> {
+        let counted: Vec<&ChoiceTally> = self
+            .choices
+            .iter()
+            .filter(|choice| choices.contains(&choice.choice_id))
+            .collect();
+        if counted.iter().all(|choice| choice.total == 0) {
+            return Vec::new();
+        }
+        let best = counted.iter().map(|choice| choice.rank).min();
+        counted
+            .iter()
+            .filter(|choice| Some(choice.rank) == best)
+            .map(|choice| choice.choice_id)
+            .collect()
+    }
+}
+
+/// What a single vote contributes to its choice's total, given how many choices the poll has.
+/// For instant runoff this is only whether it's a first preference.
+pub fn points(method: VotingMethod, value: i32, choices: usize) -> i32 {
+    match method {
+        VotingMethod::Dots
+        | VotingMethod::Approval
+        | VotingMethod::Score
+        | VotingMethod::Quadratic => value,
+        VotingMethod::Borda if value > 0 => (choices as i32 - value).max(0),
+        VotingMethod::RankedChoice if value == 1 => 1,
+        VotingMethod::Borda | VotingMethod::RankedChoice => 0,
+    }
+}
+
+pub fn tally(results: &PollResults) -> Tally {
+    let method = results.poll.voting_method;
+    let choices: Vec<ChoiceId> = results.choices.iter().map(|choice| choice.id).collect();
+    match method {
+        VotingMethod::RankedChoice => instant_runoff(&choices, &ballots(&results.votes)),
+        _ => {
+            let totals: Vec<i32> = choices
+                .iter()
+                .map(|id| {
+                    results
+                        .votes
+                        .iter()
+                        .filter(|vote| vote.choice_id == *id)
+                        .map(|vote| points(method, vote.dots, choices.len()))
+                        .sum()
+                })
+                .collect();
+            let mut ranked = ranked(&choices, &totals, &totals);
+            for choice in ranked.iter_mut() {
+                let id = choice.choice_id;
+                let votes = results.votes.iter().filter(|vote| vote.choice_id == id);
+                choice.negative = -votes
+                    .clone()
+                    .map(|vote| points(method, vote.dots, choices.len()).min(0))
+                    .sum::<i32>();
+                choice.positive = choice.total + choice.negative;
+                if method == VotingMethod::Quadratic {
+                    choice.credits = votes.map(|vote| quadratic_cost(vote.dots)).sum();
+                }
+            }
+            Tally {
+                choices: ranked,
+                rounds: Vec::new(),
+            }
+        }
+    }
+}
+
+/// Ranks choices by `keys`, highest first, and pairs them up with their totals.
+fn ranked<K: Ord>(choices: &[ChoiceId], totals: &[i32], keys: &[K]) -> Vec<ChoiceTally> {
+    choices
+        .iter()
+        .zip(totals)
+        .zip(keys)
+        .map(|((choice_id, total), key)| ChoiceTally {
+            choice_id: *choice_id,
+            total: *total,
+            credits: 0,
+            positive: *total,
+            negative: 0,
+            rank: 1 + keys.iter().filter(|other| *other > key).count(),
+            tied: keys.iter().filter(|other| *other == key).count() > 1,
+        })
+        .collect()
+}
+
+/// Each ballot's rankings, most preferred first.
+fn ballots(votes: &[Vote]) -> Vec<Vec<ChoiceId>> {
+    let mut ballots: BTreeMap<BallotId, Vec<&Vote>> = BTreeMap::new();
+    for vote in votes.iter().filter(|vote| vote.dots > 0) {
+        ballots.entry(vote.ballot).or_default().push(vote);
+    }
+    ballots
+        .into_values()
+        .map(|mut ballot| {
+            ballot.sort_by_key(|vote| vote.dots);
+            ballot.iter().map(|vote| vote.choice_id).collect()
+        })
+        .collect()
+}
+
+/// Counts first preferences, knocking out the last-placed choices and passing their ballots
+/// on until one choice holds a majority of the ballots still in play. Choices tied for last
+/// place are knocked out together, unless that would knock out everyone left, in which case
+/// they share the win.
+fn instant_runoff(choices: &[ChoiceId], ballots: &[Vec<ChoiceId>]) -> Tally {
+    let mut continuing: Vec<ChoiceId> = choices.to_vec();
+    // The last round each choice took part in and its count then, which is what ranks them
+    let mut standing: HashMap<ChoiceId, (usize, i32)> = HashMap::new();
+    let mut rounds = Vec::new();
+
+    loop {
+        let mut counts: Vec<(ChoiceId, i32)> = continuing.iter().map(|id| (*id, 0)).collect();
+        let mut exhausted = 0;
+        for ballot in ballots {
+            match ballot.iter().find(|id| continuing.contains(id)) {
+                Some(id) => {
+                    if let Some(count) = counts.iter_mut().find(|(choice, _)| choice == id) {
+                        count.1 += 1;
+                    }
+                }
+                None => exhausted += 1,
+            }
+        }
+        for (id, count) in &counts {
+            standing.insert(*id, (rounds.len(), *count));
+        }
+
+        let active: i32 = counts.iter().map(|(_, count)| count).sum();
+        let most = counts.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        let least = counts.iter().map(|(_, count)| *count).min().unwrap_or(0);
+        let eliminated: Vec<ChoiceId> = counts
+            .iter()
+            .filter(|(_, count)| *count == least)
+            .map(|(id, _)| *id)
+            .collect();
+        if most * 2 > active || eliminated.len() == counts.len() {
+            rounds.push(Round {
+                counts,
+                exhausted,
+                eliminated: Vec::new(),
+            });
+            break;
+        }
+        continuing.retain(|id| !eliminated.contains(id));
+        rounds.push(Round {
+            counts,
+            exhausted,
+            eliminated,
+        });
+    }
+
+    let keys: Vec<(usize, i32)> = choices.iter().map(|id| standing[id]).collect();
+    let totals: Vec<i32> = keys.iter().map(|(_, count)| *count).collect();
+    Tally {
+        choices: ranked(choices, &totals, &keys),
+        rounds,
+    }
+}
+
+/// How proposals get picked for funding once the votes are in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundingStrategy {
+    /// Fund the most supported proposals first, skipping any that no longer fit.
+    Greedy,
+    /// Fund whichever set of proposals that fits the budget has the most support overall.
+    Optimal,
+}
+
+/// The proposals a participatory budgeting poll funds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Funding {
+    /// In order of support.
+    pub funded: Vec<ChoiceId>,
+    pub spent: i32,
+    pub left: i32,
+}
+
+/// The most budget/cost cells the optimal strategy will work through before giving up.
+const MAX_KNAPSACK_CELLS: usize = 4_000_000;
+
+/// Picks the proposals to fund out of the poll's funding. Only choices with a cost and some
+/// support can be funded. `None` if the poll has no funding, or the optimal strategy would take
+/// too long with these numbers.
+pub fn fund(results: &PollResults, tally: &Tally, strategy: FundingStrategy) -> Option<Funding> {
+    let budget = results.poll.funding?.max(0);
+    let mut proposals: Vec<(&ChoiceTally, i32)> = results
+        .choices
+        .iter()
+        .filter_map(|choice| Some((tally.get(choice.id)?, choice.cost?.max(0))))
+        .filter(|(counted, _)| counted.total > 0)
+        .collect();
+    proposals.sort_by_key(|(counted, _)| counted.rank);
+
+    let funded: Vec<(&ChoiceTally, i32)> = match strategy {
+        FundingStrategy::Greedy => {
+            let mut left = budget;
+            proposals
+                .into_iter()
+                .filter(|(_, cost)| {
+                    let fits = *cost <= left;
+                    if fits {
+                        left -= cost;
+                    }
+                    fits
+                })
+                .collect()
+        }
+        FundingStrategy::Optimal => knapsack(&proposals, budget)?,
+    };
+    let spent = funded.iter().map(|(_, cost)| cost).sum();
+    Some(Funding {
+        funded: funded
+            .iter()
+            .map(|(counted, _)| counted.choice_id)
+            .collect(),
+        spent,
+        left: budget - spent,
+    })
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// 0/1 knapsack over support. Costs and budget are divided through by their common factor
+/// first, since budgets tend to be round numbers.
+fn knapsack<'a>(
+    proposals: &[(&'a ChoiceTally, i32)],
+    budget: i32,
+) -> Option<Vec<(&'a ChoiceTally, i32)>> {
+    let unit = proposals
+        .iter()
+        .fold(budget, |unit, (_, cost)| gcd(unit, *cost))
+        .max(1);
+    let capacity = (budget / unit) as usize;
+    // Checked, since usize is only 32 bits on wasm
+    if proposals
+        .len()
+        .checked_mul(capacity + 1)
+        .is_none_or(|cells| cells > MAX_KNAPSACK_CELLS)
+    {
+        return None;
+    }
+
+    // best[c] is the most support reachable with c units, taken[i][c] whether that used item i
+    let mut best = vec![0; capacity + 1];
+    let mut taken = vec![vec![false; capacity + 1]; proposals.len()];
+    for (i, (counted, cost)) in proposals.iter().enumerate() {
+        let weight = (*cost / unit) as usize;
+        for c in (weight..=capacity).rev() {
+            let with = best[c - weight] + counted.total;
+            if with > best[c] {
+                best[c] = with;
+                taken[i][c] = true;
+            }
+        }
+    }
+
+    let mut funded = Vec::new();
+    let mut c = capacity;
+    for (i, proposal) in proposals.iter().enumerate().rev() {
+        if taken[i][c] {
+            funded.push(*proposal);
+            c -= (proposal.1 / unit) as usize;
+        }
+    }
+    funded.reverse();
+    Some(funded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{PollChoice, PollMetadata};
+    use crate::{PollId, PollUuid, Timestamp, VoteId};
+
+    fn ids(ids: &[i32]) -> Vec<ChoiceId> {
+        ids.iter().map(|id| ChoiceId(*id)).collect()
+    }
+
+    fn results(method: VotingMethod, choices: &[i32], votes: Vec<Vote>) -> PollResults {
+        let created_at = Timestamp::from_millis(0).unwrap();
+        PollResults {
+            poll: PollMetadata {
+                id: PollId(1),
+                uuid: PollUuid::from("poll"),
+                title: "Poll".into(),
+                created_at,
+                voting_method: method,
+                dot_budget: Default::default(),
+                rules: Default::default(),
+                funding: None,
+                opens_at: None,
+                closes_at: None,
+                phase: None,
+                categories: Vec::new(),
+                results_visibility: Default::default(),
+                anonymous: false,
+                invite_only: false,
+            },
+            choices: choices
+                .iter()
+                .map(|id| PollChoice {
+                    id: ChoiceId(*id),
+                    poll_id: PollId(1),
+                    details: id.to_string(),
+                    cost: None,
+                    category: None,
+                    created_at,
+                })
+                .collect(),
+            votes,
+            participation: Vec::new(),
+        }
+    }
+
+    /// Votes for `choices`, each given the next value, all cast on `ballot` by `voter`.
+    fn ballot(ballot: i32, voter: &str, choices: &[i32]) -> Vec<Vote> {
+        choices
+            .iter()
+            .enumerate()
+            .map(|(i, choice)| Vote {
+                id: VoteId(ballot * 100 + i as i32),
+                poll_id: PollId(1),
+                choice_id: ChoiceId(*choice),
+                dots: i as i32 + 1,
+                voter: voter.into(),
+                ballot: BallotId(ballot),
+                created_at: Timestamp::from_millis(0).unwrap(),
+            })
+            .collect()
+    }
+
+    fn counts(round: &Round) -> Vec<i32> {
+        round.counts.iter().map(|(_, count)| *count).collect()
+    }
+
+    fn funded(votes: &[(i32, i32)], costs: &[i32], budget: i32) -> Vec<Option<Funding>> {
+        let choices: Vec<i32> = (1..=costs.len() as i32).collect();
+        let mut results = results(
+            VotingMethod::Dots,
+            &choices,
+            votes
+                .iter()
+                .enumerate()
+                .map(|(i, (choice, dots))| {
+                    let mut vote = ballot(i as i32 + 1, "a", &[*choice]).remove(0);
+                    vote.dots = *dots;
+                    vote
+                })
+                .collect(),
+        );
+        for (choice, cost) in results.choices.iter_mut().zip(costs) {
+            choice.cost = Some(*cost);
+        }
+        results.poll.funding = Some(budget);
+        let tally = tally(&results);
+        [FundingStrategy::Greedy, FundingStrategy::Optimal]
+            .iter()
+            .map(|strategy| fund(&results, &tally, *strategy))
+            .collect()
+    }
+
+    #[test]
+    fn optimal_funding_divides_out_shared_costs() {
+        // Greedy takes the favourite and can't fit anything else, optimal takes the other two
+        let funding = funded(
+            &[(1, 5), (2, 4), (3, 3)],
+            &[1_000_000, 600_000, 400_000],
+            1_000_000,
+        );
+        assert_eq!(
+            funding[0].as_ref().map(|f| f.funded.clone()),
+            Some(ids(&[1]))
+        );
+        let optimal = funding[1].as_ref().expect("costs share a factor of 200000");
+        assert_eq!(optimal.funded, ids(&[2, 3]));
+        assert_eq!(optimal.spent, 1_000_000);
+        assert_eq!(optimal.left, 0);
+    }
+
+    #[test]
+    fn optimal_funding_gives_up_on_huge_budgets() {
+        let funding = funded(&[(1, 2), (2, 1)], &[1, 2], i32::MAX);
+        assert_eq!(
+            funding[0].as_ref().map(|f| f.funded.clone()),
+            Some(ids(&[1, 2]))
+        );
+        assert_eq!(funding[1], None);
+    }
+
+    #[test]
+    fn points_by_method() {
+        assert_eq!(points(VotingMethod::Dots, 3, 4), 3);
+        assert_eq!(points(VotingMethod::Dots, -2, 4), -2);
+        assert_eq!(points(VotingMethod::Quadratic, 2, 4), 2);
+        assert_eq!(points(VotingMethod::Borda, 1, 4), 3);
+        assert_eq!(points(VotingMethod::Borda, 4, 4), 0);
+        assert_eq!(points(VotingMethod::Borda, 0, 4), 0);
+        assert_eq!(points(VotingMethod::RankedChoice, 1, 4), 1);
+        assert_eq!(points(VotingMethod::RankedChoice, 2, 4), 0);
+    }
+
+    #[test]
+    fn tally_ranks_totals_and_ties() {
+        let mut votes = ballot(1, "a", &[1]);
+        votes.extend(ballot(2, "b", &[2]));
+        votes.extend(ballot(3, "c", &[3, 1]));
+        let tally = tally(&results(VotingMethod::Dots, &[1, 2, 3], votes));
+        let totals: Vec<(i32, usize, bool)> = tally
+            .choices
+            .iter()
+            .map(|choice| (choice.total, choice.rank, choice.tied))
+            .collect();
+        assert_eq!(totals, vec![(3, 1, false), (1, 2, true), (1, 2, true)]);
+        assert_eq!(tally.winners(), ids(&[1]));
+    }
+
+    #[test]
+    fn instant_runoff_majority_in_first_round() {
+        let ballots = vec![ids(&[1, 2]), ids(&[1]), ids(&[2, 1])];
+        let tally = instant_runoff(&ids(&[1, 2]), &ballots);
+        assert_eq!(tally.rounds.len(), 1);
+        assert_eq!(counts(&tally.rounds[0]), vec![2, 1]);
+        assert!(tally.rounds[0].eliminated.is_empty());
+        assert_eq!(tally.winners(), ids(&[1]));
+    }
+
+    #[test]
+    fn instant_runoff_eliminates_and_transfers() {
+        let ballots = vec![ids(&[1]), ids(&[1]), ids(&[2]), ids(&[2]), ids(&[3, 2])];
+        let tally = instant_runoff(&ids(&[1, 2, 3]), &ballots);
+        assert_eq!(tally.rounds.len(), 2);
+        assert_eq!(counts(&tally.rounds[0]), vec![2, 2, 1]);
+        assert_eq!(tally.rounds[0].eliminated, ids(&[3]));
+        assert_eq!(counts(&tally.rounds[1]), vec![2, 3]);
+        assert_eq!(tally.winners(), ids(&[2]));
+        assert_eq!(tally.get(ChoiceId(3)).map(|choice| choice.rank), Some(3));
+    }
+
+    #[test]
+    fn instant_runoff_counts_exhausted_ballots() {
+        let ballots = vec![ids(&[1]), ids(&[1]), ids(&[2]), ids(&[2]), ids(&[3])];
+        let tally = instant_runoff(&ids(&[1, 2, 3]), &ballots);
+        assert_eq!(tally.rounds[0].exhausted, 0);
+        assert_eq!(tally.rounds[1].exhausted, 1);
+        // Nobody holds a majority of the four ballots still in play, so they share the win
+        assert_eq!(counts(&tally.rounds[1]), vec![2, 2]);
+        assert_eq!(tally.winners(), ids(&[1, 2]));
+    }
+
+    #[test]
+    fn instant_runoff_all_tied_final_round() {
+        let ballots = vec![ids(&[1, 2]), ids(&[2, 3]), ids(&[3, 1])];
+        let tally = instant_runoff(&ids(&[1, 2, 3]), &ballots);
+        assert_eq!(tally.rounds.len(), 1);
+        assert!(tally.rounds[0].eliminated.is_empty());
+        assert_eq!(tally.winners(), ids(&[1, 2, 3]));
+        assert!(tally.choices.iter().all(|choice| choice.tied));
+    }
+
+    #[test]
+    fn ranked_ballots_are_grouped_by_ballot_not_name() {
+        // Two different people who both called themselves "Sam"
+        let mut votes = ballot(1, "Sam", &[1, 2]);
+        votes.extend(ballot(2, "Sam", &[2, 1]));
+        votes.extend(ballot(3, "Alex", &[1, 2]));
+        let tally = tally(&results(VotingMethod::RankedChoice, &[1, 2], votes));
+        assert_eq!(counts(&tally.rounds[0]), vec![2, 1]);
+        assert_eq!(tally.winners(), ids(&[1]));
+    }
+}
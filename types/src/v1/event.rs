@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::v1::{Poll, Vote};
+use crate::VoteId;
+
+/// Something that happened to a poll, pushed to clients watching
+/// `/polls/{id}/events` as server-sent events with a JSON payload.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PollEvent {
+    /// A ballot was cast.
+    Voted { votes: Vec<Vote> },
+    /// A ballot was withdrawn, or changed, in which case its replacement follows as `Voted`.
+    Withdrawn { votes: Vec<VoteId> },
+    /// The poll's settings or choices changed, for example it was closed early. Votes on
+    /// choices that are gone were removed with them.
+    Updated { poll: Poll },
+}
@@ -0,0 +1,19 @@
+//! Version 1 of the API, served under `/api/v1`.
+mod error;
+mod event;
+mod poll;
+mod validate;
+mod vote;
+
+pub use error::ErrorBody;
+pub use event::PollEvent;
+pub use poll::{
+    quadratic_cost, AdvancePhase, Category, CreatePoll, CreatePollResponse, DotBudget, Invite,
+    MergeChoices, NewChoice, Participant, Poll, PollChoice, PollMetadata, PollPhase, PollStatus,
+    ResultsViewer, ResultsVisibility, UpdateChoice, UpdatePoll, VoteRules, VotingMethod, MAX_DOTS,
+    MAX_SCORE,
+};
+pub use vote::{PollResults, Vote, VoteReceipt, VoteSubmission};
+
+/// The path every v1 endpoint lives under.
+pub const PREFIX: &str = "/api/v1";
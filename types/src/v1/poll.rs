@@ -0,0 +1,534 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{AdminToken, ChoiceId, InviteToken, PollId, PollUuid, Timestamp};
+
+/// How voters express their preferences, and so how their votes are counted.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingMethod {
+    /// Voters spread a budget of dots over the choices, see `DotBudget` and `VoteRules`.
+    #[default]
+    Dots,
+    /// Voters approve of any number of choices.
+    Approval,
+    /// Voters give every choice a score from 0 to `MAX_SCORE`.
+    Score,
+    /// Voters rank the choices, counted by instant runoff.
+    RankedChoice,
+    /// Voters rank the choices, counted by Borda count.
+    Borda,
+    /// Voters place dots like `Dots`, but n dots on one choice cost n² credits from a budget
+    /// set by `DotBudget`.
+    Quadratic,
+}
+
+/// The highest score a choice can be given on a `VotingMethod::Score` poll.
+pub const MAX_SCORE: i32 = 5;
+
+/// The most dots, or credits, any budget or rule can hand a voter. Ballots and results draw
+/// dots one by one, so budgets much bigger than this would be unusable anyway.
+pub const MAX_DOTS: i32 = 1000;
+
+/// The credits it costs to place `votes` dots on one choice of a quadratic poll. Ballots that
+/// passed `Poll::validate_vote` can't overflow it.
+pub fn quadratic_cost(votes: i32) -> i32 {
+    votes * votes
+}
+
+impl VotingMethod {
+    /// Whether voters order the choices rather than giving each one a value.
+    pub fn is_ranked(&self) -> bool {
+        matches!(self, VotingMethod::RankedChoice | VotingMethod::Borda)
+    }
+
+    /// Whether voters spend a budget from the poll's `DotBudget`.
+    pub fn has_budget(&self) -> bool {
+        matches!(self, VotingMethod::Dots | VotingMethod::Quadratic)
+    }
+}
+
+/// How many dots each voter gets to allocate on a poll.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DotBudget {
+    /// Every voter gets the same number of dots.
+    Fixed { dots: i32 },
+    /// Every voter gets one dot for every `choices_per_dot` choices, rounded up.
+    Scaled { choices_per_dot: i32 },
+}
+
+impl DotBudget {
+    /// The number of dots a voter gets on a poll with `choices` choices.
+    pub fn dots(&self, choices: usize) -> i32 {
+        match *self {
+            DotBudget::Fixed { dots } => dots.max(1),
+            DotBudget::Scaled { choices_per_dot } => {
+                let choices_per_dot = choices_per_dot.max(1) as usize;
+                choices.div_ceil(choices_per_dot).max(1) as i32
+            }
+        }
+    }
+}
+
+impl Default for DotBudget {
+    // Polls created before budgets were configurable always had two dots
+    fn default() -> Self {
+        DotBudget::Fixed { dots: 2 }
+    }
+}
+
+/// Restrictions on how a voter may spread their dots across the choices of a poll.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct VoteRules {
+    /// The most dots a voter may place on a single choice.
+    pub max_per_choice: Option<i32>,
+    /// The fewest distinct choices a voter must place dots on.
+    pub min_choices: Option<i32>,
+    /// Voters may place at most one dot on each choice.
+    pub no_stacking: bool,
+    /// Dots voters may place against choices, separate from their budget. Only dot voting
+    /// polls allow them.
+    pub negative_dots: Option<i32>,
+}
+
+impl VoteRules {
+    /// The most dots a voter may place on a single choice, if limited.
+    pub fn dot_cap(&self) -> Option<i32> {
+        if self.no_stacking {
+            Some(1)
+        } else {
+            self.max_per_choice.map(|max| max.max(1))
+        }
+    }
+}
+
+/// A group of choices, like "Went well" on a retro board.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Category {
+    pub name: String,
+    /// Dots, or credits on quadratic polls, that each voter spends on this category's choices
+    /// instead of the poll's own budget. Unset to share the poll's budget.
+    #[serde(default)]
+    pub dots: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PollChoice {
+    pub id: ChoiceId,
+    pub poll_id: PollId,
+    pub details: String,
+    /// What funding the choice would take, for participatory budgeting.
+    #[serde(default)]
+    pub cost: Option<i32>,
+    /// Index into the poll's categories, unset for choices outside them.
+    #[serde(default)]
+    pub category: Option<usize>,
+    pub created_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PollMetadata {
+    pub id: PollId,
+    pub uuid: PollUuid,
+    pub title: String,
+    pub created_at: Timestamp,
+    #[serde(default)]
+    pub voting_method: VotingMethod,
+    #[serde(default)]
+    pub dot_budget: DotBudget,
+    #[serde(default)]
+    pub rules: VoteRules,
+    /// The budget to fund choices out of, turning the poll into participatory budgeting.
+    #[serde(default)]
+    pub funding: Option<i32>,
+    /// Voting starts here, or straight away if unset.
+    #[serde(default)]
+    pub opens_at: Option<Timestamp>,
+    /// Voting ends here, or never if unset. Closing a poll early moves this to the present.
+    #[serde(default)]
+    pub closes_at: Option<Timestamp>,
+    /// Where a brainstorming poll is up to. Unset for polls created with their choices, which
+    /// take votes and show results throughout.
+    #[serde(default)]
+    pub phase: Option<PollPhase>,
+    /// Groups the choices are shown in. Empty for a single flat list.
+    #[serde(default)]
+    pub categories: Vec<Category>,
+    #[serde(default)]
+    pub results_visibility: ResultsVisibility,
+    /// Voters don't give their names. Their names are never stored, and each ballot's votes
+    /// carry a meaningless label instead, which only tells ballots apart. Vote and ballot ids
+    /// are random too, so neither they nor the order votes come in say when anyone voted.
+    #[serde(default)]
+    pub anonymous: bool,
+    /// Only the people on the poll's roster can vote, each once, through their own invite.
+    #[serde(default)]
+    pub invite_only: bool,
+}
+
+/// Who can see a poll's results, and from when. Hiding them stops early results swaying
+/// later voters. The creator can always see them on the poll's admin page.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultsVisibility {
+    /// Anyone, at any time.
+    #[default]
+    Always,
+    /// Voters once they've cast a ballot, and everyone after voting closes.
+    AfterVote,
+    /// Everyone once voting closes.
+    AfterClose,
+    /// Only the creator.
+    CreatorOnly,
+}
+
+/// Who is asking for a poll's results, told apart by the token they send along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsViewer {
+    Anyone,
+    /// Someone holding the edit token of a ballot on the poll.
+    Voter,
+    /// Someone holding the poll's admin token.
+    Creator,
+}
+
+/// Where a poll is in its life, see `PollMetadata::status`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PollStatus {
+    /// Not open for votes yet.
+    Scheduled,
+    Open,
+    /// Voting has ended and the results are final.
+    Closed,
+}
+
+/// The stages of a brainstorming poll, moved through in order by the facilitator, who holds
+/// the admin token.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PollPhase {
+    /// Participants add choices. Nobody can vote yet.
+    Collect,
+    /// The choices are locked and voting is on, with the results hidden.
+    Vote,
+    /// Voting has ended and the results are shown.
+    Reveal,
+}
+
+impl PollPhase {
+    /// The phase that follows this one, if any.
+    pub fn next(self) -> Option<PollPhase> {
+        match self {
+            PollPhase::Collect => Some(PollPhase::Vote),
+            PollPhase::Vote => Some(PollPhase::Reveal),
+            PollPhase::Reveal => None,
+        }
+    }
+}
+
+impl PollMetadata {
+    /// Revealing a brainstorming poll's results closes it for good.
+    pub fn status(&self, now: Timestamp) -> PollStatus {
+        if self.phase == Some(PollPhase::Reveal)
+            || self.closes_at.is_some_and(|closes_at| closes_at <= now)
+        {
+            PollStatus::Closed
+        } else if self.opens_at.is_some_and(|opens_at| now < opens_at) {
+            PollStatus::Scheduled
+        } else {
+            PollStatus::Open
+        }
+    }
+
+    /// Whether participants can add choices.
+    pub fn collecting(&self) -> bool {
+        self.phase == Some(PollPhase::Collect)
+    }
+
+    /// Whether ballots can be cast, changed or withdrawn.
+    pub fn taking_votes(&self, now: Timestamp) -> bool {
+        self.status(now) == PollStatus::Open && !self.collecting()
+    }
+
+    /// Whether the results can be shown. Brainstorming polls hide them until they're revealed.
+    pub fn results_revealed(&self) -> bool {
+        matches!(self.phase, None | Some(PollPhase::Reveal))
+    }
+
+    /// Whether `viewer` may see the results at `now`, under the poll's `ResultsVisibility`.
+    pub fn results_visible_to(&self, viewer: ResultsViewer, now: Timestamp) -> bool {
+        let closed = self.status(now) == PollStatus::Closed;
+        self.results_revealed()
+            && match self.results_visibility {
+                ResultsVisibility::Always => true,
+                ResultsVisibility::AfterVote => viewer != ResultsViewer::Anyone || closed,
+                ResultsVisibility::AfterClose => viewer == ResultsViewer::Creator || closed,
+                ResultsVisibility::CreatorOnly => viewer == ResultsViewer::Creator,
+            }
+    }
+
+    /// Why the results are hidden from someone `results_visible_to` turns away.
+    pub fn results_hidden_reason(&self) -> &'static str {
+        if !self.results_revealed() {
+            return "the results are hidden until the facilitator reveals them";
+        }
+        match self.results_visibility {
+            ResultsVisibility::Always => "the results are hidden",
+            ResultsVisibility::AfterVote => "the results are shown once you've voted",
+            ResultsVisibility::AfterClose => "the results are shown once voting closes",
+            ResultsVisibility::CreatorOnly => "only the poll's creator can see the results",
+        }
+    }
+
+    /// The most dots a voter may place on a single choice, if limited. Only dot voting polls
+    /// have a cap: quadratic credits aren't dots, so it can't apply to them.
+    pub fn dot_cap(&self) -> Option<i32> {
+        match self.voting_method {
+            VotingMethod::Dots => self.rules.dot_cap(),
+            _ => None,
+        }
+    }
+
+    /// How many dots each voter may place against choices, if any.
+    pub fn negative_dots(&self) -> Option<i32> {
+        match self.voting_method {
+            VotingMethod::Dots => self.rules.negative_dots.filter(|dots| *dots > 0),
+            _ => None,
+        }
+    }
+
+    /// `choices` grouped by category, in the poll's order, then those outside every category
+    /// as `None`. Empty groups are left out.
+    pub fn group_choices<'a>(
+        &self,
+        choices: &'a [PollChoice],
+    ) -> Vec<(Option<usize>, Vec<&'a PollChoice>)> {
+        let category_of = |choice: &PollChoice| {
+            choice
+                .category
+                .filter(|category| *category < self.categories.len())
+        };
+        (0..self.categories.len())
+            .map(Some)
+            .chain(Some(None))
+            .map(|category| {
+                let members: Vec<&PollChoice> = choices
+                    .iter()
+                    .filter(|choice| category_of(choice) == category)
+                    .collect();
+                (category, members)
+            })
+            .filter(|(_, members)| !members.is_empty())
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Poll {
+    pub poll: PollMetadata,
+    pub choices: Vec<PollChoice>,
+}
+
+impl Poll {
+    /// The number of dots each voter must allocate from the poll's own budget. A per-choice cap
+    /// can make the configured budget unreachable, in which case voters only get as many dots
+    /// as they can place.
+    pub fn dots_per_voter(&self) -> i32 {
+        self.budget_dots(None)
+    }
+
+    /// Which budget dots on `choice` come out of: its category's, if that has its own, or the
+    /// poll's as `None`. Only polls with a budget split it up.
+    pub fn budget_of(&self, choice: &PollChoice) -> Option<usize> {
+        if !self.poll.voting_method.has_budget() {
+            return None;
+        }
+        choice.category.filter(|category| {
+            self.poll
+                .categories
+                .get(*category)
+                .is_some_and(|category| category.dots.is_some())
+        })
+    }
+
+    /// The dots each voter must allocate from `budget`, see `budget_of`.
+    pub fn budget_dots(&self, budget: Option<usize>) -> i32 {
+        let choices = self
+            .choices
+            .iter()
+            .filter(|choice| self.budget_of(choice) == budget)
+            .count();
+        let dots = match budget.and_then(|category| self.poll.categories.get(category)?.dots) {
+            Some(dots) => dots.max(1),
+            None => self.poll.dot_budget.dots(choices),
+        };
+        match self.poll.dot_cap() {
+            Some(cap) => dots.min(cap.saturating_mul(choices as i32)),
+            None => dots,
+        }
+    }
+
+    /// Every budget with choices to spend it on, the poll's own first.
+    pub fn budgets(&self) -> Vec<Option<usize>> {
+        let mut budgets: Vec<Option<usize>> = Vec::new();
+        for choice in &self.choices {
+            let budget = self.budget_of(choice);
+            if !budgets.contains(&budget) {
+                budgets.push(budget);
+            }
+        }
+        budgets.sort();
+        budgets
+    }
+
+    /// The number of distinct choices each voter must place a dot on.
+    pub fn min_choices(&self) -> i32 {
+        self.poll
+            .rules
+            .min_choices
+            .unwrap_or(0)
+            .min(self.choices.len() as i32)
+            .min(self.dots_per_voter())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreatePoll {
+    pub title: String,
+    pub choices: Vec<String>,
+    #[serde(default)]
+    pub voting_method: VotingMethod,
+    pub dot_budget: DotBudget,
+    pub rules: VoteRules,
+    /// Costs for `choices`, matched up by index. Missing entries mean no cost.
+    #[serde(default)]
+    pub costs: Vec<Option<i32>>,
+    #[serde(default)]
+    pub funding: Option<i32>,
+    #[serde(default)]
+    pub opens_at: Option<Timestamp>,
+    #[serde(default)]
+    pub closes_at: Option<Timestamp>,
+    /// Start by collecting choices from participants, see `PollPhase`. `choices` can then have
+    /// fewer than two entries.
+    #[serde(default)]
+    pub brainstorm: bool,
+    #[serde(default)]
+    pub categories: Vec<Category>,
+    /// Categories for `choices`, matched up by index. Missing entries mean no category.
+    #[serde(default)]
+    pub choice_categories: Vec<Option<usize>>,
+    #[serde(default)]
+    pub results_visibility: ResultsVisibility,
+    #[serde(default)]
+    pub anonymous: bool,
+    /// The names of the people invited to vote. Any at all makes the poll invite only, with an
+    /// invite for each handed back in `CreatePollResponse::invites`.
+    #[serde(default)]
+    pub roster: Vec<String>,
+}
+
+impl CreatePoll {
+    /// The roster's names that aren't blank, trimmed.
+    pub fn roster_names(&self) -> impl Iterator<Item = &str> {
+        self.roster
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// The choices that aren't blank, trimmed, with their costs and categories.
+    pub fn new_choices(&self) -> impl Iterator<Item = (&str, Option<i32>, Option<usize>)> {
+        self.choices
+            .iter()
+            .enumerate()
+            .map(move |(i, details)| {
+                (
+                    details.trim(),
+                    self.costs.get(i).cloned().flatten(),
+                    self.choice_categories.get(i).cloned().flatten(),
+                )
+            })
+            .filter(|(details, _, _)| !details.is_empty())
+    }
+}
+
+/// A creator's edit to a poll. `choices` is the whole new list: entries with an id keep that
+/// choice under new details, entries without one are added, and choices left out are removed
+/// along with their votes.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UpdatePoll {
+    pub title: String,
+    pub choices: Vec<UpdateChoice>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct UpdateChoice {
+    #[serde(default)]
+    pub id: Option<ChoiceId>,
+    pub details: String,
+    #[serde(default)]
+    pub cost: Option<i32>,
+    #[serde(default)]
+    pub category: Option<usize>,
+}
+
+impl UpdatePoll {
+    /// The choices that aren't blank. Blanking an existing choice removes it.
+    pub fn listed_choices(&self) -> impl Iterator<Item = &UpdateChoice> {
+        self.choices
+            .iter()
+            .filter(|choice| !choice.details.trim().is_empty())
+    }
+}
+
+/// A choice added by a participant while a poll is collecting them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewChoice {
+    pub details: String,
+    #[serde(default)]
+    pub category: Option<usize>,
+}
+
+/// Moves a brainstorming poll on to `phase`, which must be the one after its current phase.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AdvancePhase {
+    pub phase: PollPhase,
+    /// Duplicate choices to fold together when collecting ends.
+    #[serde(default)]
+    pub merges: Vec<MergeChoices>,
+}
+
+/// Keeps `into` and removes the choices in `from`, which said the same thing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MergeChoices {
+    pub into: ChoiceId,
+    pub from: Vec<ChoiceId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreatePollResponse {
+    pub poll: PollUuid,
+    /// Lets the creator close and manage the poll. Only handed out once, here.
+    #[serde(default)]
+    pub admin_token: Option<AdminToken>,
+    /// An invite for each name on the roster, for the creator to hand out.
+    #[serde(default)]
+    pub invites: Vec<Invite>,
+}
+
+/// One person on a poll's roster, and the token in their invite link.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Invite {
+    pub name: String,
+    pub token: InviteToken,
+}
+
+/// Whether someone on a poll's roster has voted yet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub name: String,
+    pub voted: bool,
+}
@@ -0,0 +1,265 @@
+//! Checks on what creators and participants send, shared by the server and the mock backend
+//! so they can't drift apart. Ballots are checked by `Poll::validate_vote`.
+//!
+//! Problems are keyed by the field they concern, like `ErrorBody::fields`, and an empty map
+//! means the request is fine. Whether someone may make the request at all is up to the caller.
+
+use std::collections::BTreeMap;
+
+use crate::v1::{
+    AdvancePhase, Category, CreatePoll, DotBudget, NewChoice, Poll, PollPhase, UpdatePoll, MAX_DOTS,
+};
+use crate::Timestamp;
+
+/// Collects problems keyed by field.
+#[derive(Default)]
+struct Problems(BTreeMap<String, Vec<String>>);
+
+impl Problems {
+    fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0
+            .entry(field.to_owned())
+            .or_default()
+            .push(message.into());
+    }
+}
+
+/// Whether `category` is one of `categories`, or no category at all.
+fn known_category(categories: &[Category], category: Option<usize>) -> bool {
+    category.is_none_or(|category| category < categories.len())
+}
+
+impl CreatePoll {
+    /// Checks the poll makes sense before it's created, with `now` to check its times against.
+    pub fn validate(&self, now: Timestamp) -> BTreeMap<String, Vec<String>> {
+        let mut problems = Problems::default();
+        if self.title.trim().is_empty() {
+            problems.add("title", "can't be blank");
+        }
+        if !self.brainstorm && self.new_choices().count() < 2 {
+            problems.add("choices", "at least two choices are needed");
+        }
+        if self
+            .new_choices()
+            .any(|(_, cost, _)| cost.is_some_and(|cost| cost < 0))
+        {
+            problems.add("costs", "can't be negative");
+        }
+        if self
+            .categories
+            .iter()
+            .any(|category| category.name.trim().is_empty())
+        {
+            problems.add("categories", "can't be blank");
+        }
+        if let DotBudget::Fixed { dots } = self.dot_budget {
+            if dots > MAX_DOTS {
+                problems.add("dot_budget", format!("at most {} dots", MAX_DOTS));
+            }
+        }
+        let rules = [self.rules.max_per_choice, self.rules.negative_dots];
+        if rules.iter().flatten().any(|dots| *dots > MAX_DOTS) {
+            problems.add("rules", format!("at most {} dots", MAX_DOTS));
+        }
+        if self
+            .categories
+            .iter()
+            .any(|category| category.dots.is_some_and(|dots| dots <= 0))
+        {
+            problems.add("categories", "dots must be more than 0");
+        }
+        if self
+            .categories
+            .iter()
+            .any(|category| category.dots.is_some_and(|dots| dots > MAX_DOTS))
+        {
+            problems.add("categories", format!("at most {} dots", MAX_DOTS));
+        }
+        if self
+            .new_choices()
+            .any(|(_, _, category)| !known_category(&self.categories, category))
+        {
+            problems.add(
+                "categories",
+                "choices must be in one of the poll's categories",
+            );
+        }
+        let roster: Vec<&str> = self.roster_names().collect();
+        if roster.iter().enumerate().any(|(i, name)| {
+            roster[..i]
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(name))
+        }) {
+            problems.add("roster", "names must all be different");
+        }
+        // Invites are tied to the ballots they cast, which would put names back on them
+        if self.anonymous && !roster.is_empty() {
+            problems.add("roster", "anonymous polls can't have a roster");
+        }
+        if self.funding.is_some_and(|funding| funding <= 0) {
+            problems.add("funding", "must be more than 0");
+        }
+        if let Some(closes_at) = self.closes_at {
+            if closes_at <= now {
+                problems.add("closes_at", "must be in the future");
+            } else if self.opens_at.is_some_and(|opens_at| closes_at <= opens_at) {
+                problems.add("closes_at", "must be after the poll opens");
+            }
+        }
+        problems.0
+    }
+}
+
+impl Poll {
+    /// Checks a creator's edit to this poll.
+    pub fn validate_update(&self, update: &UpdatePoll) -> BTreeMap<String, Vec<String>> {
+        let mut problems = Problems::default();
+        if update.title.trim().is_empty() {
+            problems.add("title", "can't be blank");
+        }
+        if update.listed_choices().count() < 2 {
+            problems.add("choices", "at least two choices are needed");
+        }
+        if update
+            .listed_choices()
+            .any(|choice| choice.cost.is_some_and(|cost| cost < 0))
+        {
+            problems.add("costs", "can't be negative");
+        }
+        for choice in update.listed_choices() {
+            if choice
+                .id
+                .is_some_and(|id| !self.choices.iter().any(|c| c.id == id))
+            {
+                problems.add(
+                    "choices",
+                    format!("\"{}\" isn't a choice on this poll", choice.details.trim()),
+                );
+            }
+        }
+        if update
+            .listed_choices()
+            .any(|choice| !known_category(&self.poll.categories, choice.category))
+        {
+            problems.add(
+                "categories",
+                "choices must be in one of the poll's categories",
+            );
+        }
+        problems.0
+    }
+
+    /// Checks a choice a participant suggests while this poll is collecting them.
+    pub fn validate_new_choice(&self, new: &NewChoice) -> BTreeMap<String, Vec<String>> {
+        let mut problems = Problems::default();
+        if new.details.trim().is_empty() {
+            problems.add("details", "can't be blank");
+        } else if self
+            .choices
+            .iter()
+            .any(|choice| choice.details.eq_ignore_ascii_case(new.details.trim()))
+        {
+            problems.add("details", "is already on the poll");
+        }
+        if !known_category(&self.poll.categories, new.category) {
+            problems.add("category", "isn't one of the poll's categories");
+        }
+        problems.0
+    }
+
+    /// Checks moving this brainstorming poll on to its next phase.
+    pub fn validate_advance(&self, advance: &AdvancePhase) -> BTreeMap<String, Vec<String>> {
+        let mut problems = Problems::default();
+        let current = match self.poll.phase {
+            Some(current) => current,
+            None => {
+                problems.add("phase", "this poll isn't brainstorming");
+                return problems.0;
+            }
+        };
+        if current.next() != Some(advance.phase) {
+            problems.add(
+                "phase",
+                "polls go from collecting choices, to voting, to revealing results",
+            );
+        }
+        if !advance.merges.is_empty() && current != PollPhase::Collect {
+            problems.add("merges", "choices can only be merged when collecting ends");
+        }
+        let known = |choice| self.choices.iter().any(|c| c.id == choice);
+        if advance.merges.iter().any(|merge| {
+            !known(merge.into)
+                || merge.from.contains(&merge.into)
+                || merge.from.iter().any(|choice| !known(*choice))
+        }) {
+            problems.add("merges", "can only merge different choices on this poll");
+        }
+        let remaining = self
+            .choices
+            .iter()
+            .filter(|choice| {
+                !advance
+                    .merges
+                    .iter()
+                    .any(|merge| merge.from.contains(&choice.id))
+            })
+            .count();
+        if advance.phase == PollPhase::Vote && remaining < 2 {
+            problems.add("choices", "at least two choices are needed to vote");
+        }
+        problems.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::{json, Value};
+
+    fn create(extra: Value) -> CreatePoll {
+        let mut poll = json!({
+            "title": "Lunch",
+            "choices": ["Pizza", "Tacos"],
+            "dot_budget": {"type": "fixed", "dots": 3},
+            "rules": {},
+        });
+        if let (Some(poll), Some(extra)) = (poll.as_object_mut(), extra.as_object()) {
+            poll.extend(extra.clone());
+        }
+        serde_json::from_value(poll).unwrap()
+    }
+
+    fn fields(create: &CreatePoll) -> Vec<String> {
+        let now = Timestamp::from_millis(0).unwrap();
+        create.validate(now).into_keys().collect()
+    }
+
+    #[test]
+    fn sensible_polls_pass() {
+        assert!(fields(&create(json!({}))).is_empty());
+    }
+
+    #[test]
+    fn budgets_and_rules_are_capped() {
+        let most = MAX_DOTS;
+        let over = MAX_DOTS + 1;
+        let budget = |dots| create(json!({"dot_budget": {"type": "fixed", "dots": dots}}));
+        assert!(fields(&budget(most)).is_empty());
+        assert_eq!(fields(&budget(over)), ["dot_budget"]);
+        let cap = create(json!({"rules": {"max_per_choice": over}}));
+        assert_eq!(fields(&cap), ["rules"]);
+        let against = create(json!({"rules": {"negative_dots": over}}));
+        assert_eq!(fields(&against), ["rules"]);
+        let category = create(json!({"categories": [{"name": "Food", "dots": over}]}));
+        assert_eq!(fields(&category), ["categories"]);
+    }
+
+    #[test]
+    fn anonymous_polls_have_no_roster() {
+        let poll = create(json!({"anonymous": true, "roster": ["Ana"]}));
+        assert_eq!(fields(&poll), ["roster"]);
+        let blank = create(json!({"anonymous": true, "roster": [" "]}));
+        assert!(fields(&blank).is_empty());
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::v1::{
+    quadratic_cost, Participant, Poll, PollChoice, PollMetadata, VotingMethod, MAX_SCORE,
+};
+use crate::{BallotId, ChoiceId, EditToken, InviteToken, PollId, Timestamp, VoteId};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoteSubmission {
+    /// The voter's name, ignored on anonymous and invite only polls.
+    pub voter: String,
+    /// A value per choice, whose meaning depends on the poll's `VotingMethod`: dots, 1 for
+    /// approved, a score, or a rank starting from 1. Choices left out or at 0 get nothing.
+    /// Negative dots are dots against a choice, see `VoteRules::negative_dots`.
+    pub choices: HashMap<ChoiceId, i32>,
+    /// The voter's invite, which invite only polls need. Their name comes from it instead.
+    #[serde(default)]
+    pub invite: Option<InviteToken>,
+}
+
+/// What casting a ballot returns. Keep the token to change or withdraw the ballot later.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VoteReceipt {
+    pub edit_token: EditToken,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Vote {
+    pub id: VoteId,
+    pub poll_id: PollId,
+    pub choice_id: ChoiceId,
+    /// The value the voter gave the choice, see `VoteSubmission::choices`.
+    pub dots: i32,
+    /// Who cast the ballot, or on anonymous polls a label that only tells ballots apart.
+    pub voter: String,
+    /// The ballot this vote was cast in. Group votes by this rather than `voter`, which voters
+    /// type in themselves.
+    #[serde(default)]
+    pub ballot: BallotId,
+    pub created_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PollResults {
+    pub poll: PollMetadata,
+    pub choices: Vec<PollChoice>,
+    pub votes: Vec<Vote>,
+    /// Everyone on the roster and whether they've voted, empty for polls without one.
+    #[serde(default)]
+    pub participation: Vec<Participant>,
+}
+
+impl Poll {
+    /// Checks a ballot against this poll's voting method, budget and rules. Problems are keyed
+    /// by the field they concern, an empty map means the ballot is fine.
+    pub fn validate_vote(&self, vote: &VoteSubmission) -> BTreeMap<String, Vec<String>> {
+        let mut problems = BTreeMap::new();
+        let mut problem = |field: &str, message: String| {
+            problems
+                .entry(field.to_owned())
+                .or_insert_with(Vec::new)
+                .push(message)
+        };
+
+        if vote.voter.trim().is_empty() && !self.poll.anonymous && !self.poll.invite_only {
+            problem("voter", "can't be blank".into());
+        }
+        for choice_id in vote.choices.keys() {
+            if !self.choices.iter().any(|choice| choice.id == *choice_id) {
+                problem(
+                    "choices",
+                    format!("{} isn't a choice on this poll", choice_id),
+                );
+            }
+        }
+        // Summed wider than the values so huge ones can't wrap around to something allowed
+        let against: i64 = vote
+            .choices
+            .values()
+            .filter(|value| **value < 0)
+            .map(|value| i64::from(*value))
+            .sum();
+        match self.poll.negative_dots() {
+            Some(allowed) if -against > i64::from(allowed) => problem(
+                "choices",
+                format!("at most {} dots can be placed against choices", allowed),
+            ),
+            None if against < 0 => problem("choices", "values can't be negative".into()),
+            _ => {}
+        }
+        let given = vote.choices.values().filter(|value| **value > 0).count();
+
+        match self.poll.voting_method {
+            VotingMethod::Dots => self.validate_dots(vote, &mut problem),
+            VotingMethod::Approval => {
+                if vote.choices.values().any(|value| *value > 1) {
+                    problem("choices", "approvals must be 0 or 1".into());
+                }
+                if given == 0 {
+                    problem("choices", "approve of at least one choice".into());
+                }
+            }
+            VotingMethod::Score => {
+                if vote.choices.values().any(|value| *value > MAX_SCORE) {
+                    problem("choices", format!("scores go up to {}", MAX_SCORE));
+                }
+                if given == 0 {
+                    problem("choices", "score at least one choice".into());
+                }
+            }
+            VotingMethod::Quadratic => {
+                for budget in self.budgets() {
+                    let credits = self.budget_dots(budget);
+                    // No single choice can take more dots than this, and bounding them first
+                    // keeps their costs from overflowing
+                    let most = credits.max(0).isqrt();
+                    let votes: Vec<i32> = self
+                        .budget_votes(vote, budget)
+                        .filter(|votes| *votes > 0)
+                        .collect();
+                    if votes.iter().any(|votes| *votes > most) {
+                        problem(
+                            "choices",
+                            format!(
+                                "at most {} dots per choice{}, which cost {} credits",
+                                most,
+                                self.budget_name(budget),
+                                quadratic_cost(most)
+                            ),
+                        );
+                        continue;
+                    }
+                    let spent: i32 = votes.into_iter().map(quadratic_cost).sum();
+                    if spent > credits {
+                        problem(
+                            "choices",
+                            format!(
+                                "that costs {} credits{}, only {} are available",
+                                spent,
+                                self.budget_name(budget),
+                                credits
+                            ),
+                        );
+                    }
+                }
+                if given == 0 {
+                    problem("choices", "vote for at least one choice".into());
+                }
+            }
+            VotingMethod::RankedChoice | VotingMethod::Borda => {
+                let mut ranks: Vec<i32> = vote
+                    .choices
+                    .values()
+                    .cloned()
+                    .filter(|rank| *rank > 0)
+                    .collect();
+                ranks.sort_unstable();
+                if ranks.is_empty() {
+                    problem("choices", "rank at least one choice".into());
+                } else if ranks
+                    .iter()
+                    .zip(1..)
+                    .any(|(rank, expected)| *rank != expected)
+                {
+                    problem("choices", "ranks must run 1, 2, 3... without gaps".into());
+                }
+            }
+        }
+        problems
+    }
+
+    /// The values `vote` gives the choices spending from `budget`.
+    fn budget_votes<'a>(
+        &'a self,
+        vote: &'a VoteSubmission,
+        budget: Option<usize>,
+    ) -> impl Iterator<Item = i32> + 'a {
+        vote.choices.iter().filter_map(move |(id, value)| {
+            let choice = self.choices.iter().find(|choice| choice.id == *id)?;
+            (self.budget_of(choice) == budget).then_some(*value)
+        })
+    }
+
+    /// How problems with `budget` say which one they mean.
+    fn budget_name(&self, budget: Option<usize>) -> String {
+        match budget.and_then(|category| self.poll.categories.get(category)) {
+            Some(category) => format!(" in \"{}\"", category.name),
+            None => "".into(),
+        }
+    }
+
+    fn validate_dots<F: FnMut(&str, String)>(&self, vote: &VoteSubmission, problem: &mut F) {
+        if let Some(cap) = self.poll.dot_cap() {
+            if vote.choices.values().any(|dots| *dots > cap) {
+                problem("choices", format!("at most {} dots per choice", cap));
+            }
+        }
+        for budget in self.budgets() {
+            let dots = self.budget_dots(budget);
+            let total: i64 = self
+                .budget_votes(vote, budget)
+                .filter(|dots| *dots > 0)
+                .map(i64::from)
+                .sum();
+            if total != i64::from(dots) {
+                problem(
+                    "choices",
+                    format!(
+                        "all {} dots{} must be allocated",
+                        dots,
+                        self.budget_name(budget)
+                    ),
+                );
+            }
+        }
+        let spread = vote.choices.values().filter(|dots| **dots > 0).count() as i32;
+        if spread < self.min_choices() {
+            problem(
+                "choices",
+                format!("dots must be spread over {} choices", self.min_choices()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v1::{DotBudget, VoteRules};
+    use crate::PollUuid;
+
+    /// A three choice poll, with choices 1 to 3 and a fixed budget of `dots`.
+    fn poll(method: VotingMethod, dots: i32, rules: VoteRules) -> Poll {
+        let created_at = Timestamp::from_millis(0).unwrap();
+        Poll {
+            poll: PollMetadata {
+                id: PollId(1),
+                uuid: PollUuid::from("poll"),
+                title: "Poll".into(),
+                created_at,
+                voting_method: method,
+                dot_budget: DotBudget::Fixed { dots },
+                rules,
+                funding: None,
+                opens_at: None,
+                closes_at: None,
+                phase: None,
+                categories: Vec::new(),
+                results_visibility: Default::default(),
+                anonymous: false,
+                invite_only: false,
+            },
+            choices: (1..=3)
+                .map(|id| PollChoice {
+                    id: ChoiceId(id),
+                    poll_id: PollId(1),
+                    details: id.to_string(),
+                    cost: None,
+                    category: None,
+                    created_at,
+                })
+                .collect(),
+        }
+    }
+
+    /// A ballot giving choice 1 the first value, choice 2 the second and so on.
+    fn vote(values: &[i32]) -> VoteSubmission {
+        VoteSubmission {
+            voter: "Ana".into(),
+            choices: values
+                .iter()
+                .zip(1..)
+                .map(|(value, id)| (ChoiceId(id), *value))
+                .collect(),
+            invite: None,
+        }
+    }
+
+    fn accepts(poll: &Poll, values: &[i32]) -> bool {
+        poll.validate_vote(&vote(values)).is_empty()
+    }
+
+    #[test]
+    fn dots_must_use_the_whole_budget() {
+        let poll = poll(VotingMethod::Dots, 5, VoteRules::default());
+        assert!(accepts(&poll, &[2, 2, 1]));
+        assert!(!accepts(&poll, &[2, 2, 0]));
+        assert!(!accepts(&poll, &[3, 2, 1]));
+        assert!(!accepts(&poll, &[5, 0, -1]));
+    }
+
+    #[test]
+    fn dots_follow_the_rules() {
+        let rules = VoteRules {
+            max_per_choice: Some(2),
+            min_choices: Some(3),
+            ..VoteRules::default()
+        };
+        let capped = poll(VotingMethod::Dots, 5, rules);
+        assert!(accepts(&capped, &[2, 2, 1]));
+        assert!(!accepts(&capped, &[3, 1, 1]));
+        let spread = poll(
+            VotingMethod::Dots,
+            5,
+            VoteRules {
+                min_choices: Some(3),
+                ..VoteRules::default()
+            },
+        );
+        assert!(!accepts(&spread, &[4, 1, 0]));
+    }
+
+    #[test]
+    fn dots_against_are_limited() {
+        let rules = VoteRules {
+            negative_dots: Some(2),
+            ..VoteRules::default()
+        };
+        let poll = poll(VotingMethod::Dots, 3, rules);
+        assert!(accepts(&poll, &[3, -1, -1]));
+        assert!(!accepts(&poll, &[3, -2, -1]));
+    }
+
+    #[test]
+    fn huge_values_cant_wrap_around() {
+        let rules = VoteRules {
+            negative_dots: Some(2),
+            ..VoteRules::default()
+        };
+        let against = poll(VotingMethod::Dots, 3, rules);
+        let half = i32::MIN / 2 - 1;
+        assert!(!accepts(&against, &[3, half, half]));
+        assert!(!accepts(&against, &[3, i32::MIN, i32::MIN]));
+
+        // 2 * (2^31 - 1) + 5 wraps to 3 in 32 bits
+        let total = poll(VotingMethod::Dots, 3, VoteRules::default());
+        assert!(!accepts(&total, &[i32::MAX, i32::MAX, 5]));
+    }
+
+    #[test]
+    fn quadratic_costs_are_squared() {
+        let poll = poll(VotingMethod::Quadratic, 9, VoteRules::default());
+        assert!(accepts(&poll, &[2, 2, 1]));
+        assert!(accepts(&poll, &[3, 0, 0]));
+        assert!(!accepts(&poll, &[2, 2, 2]));
+        assert!(!accepts(&poll, &[0, 0, 0]));
+    }
+
+    #[test]
+    fn huge_quadratic_votes_are_rejected() {
+        let poll = poll(VotingMethod::Quadratic, 9, VoteRules::default());
+        // 46341² overflows 32 bits, and so does 65536², right back round to 0
+        assert!(!accepts(&poll, &[46341, 0, 0]));
+        assert!(!accepts(&poll, &[65536, 1, 0]));
+        assert!(!accepts(&poll, &[i32::MAX, 0, 0]));
+        assert!(!accepts(&poll, &[3, i32::MIN, 0]));
+    }
+
+    #[test]
+    fn unknown_choices_and_blank_voters_are_rejected() {
+        let poll = poll(VotingMethod::Approval, 2, VoteRules::default());
+        assert!(accepts(&poll, &[1, 0, 1]));
+        assert!(!accepts(&poll, &[1, 0, 1, 1]));
+        let mut blank = vote(&[1, 0, 0]);
+        blank.voter = " ".into();
+        assert!(poll.validate_vote(&blank).contains_key("voter"));
+    }
+
+    #[test]
+    fn approvals_and_scores_stay_in_range() {
+        let approval = poll(VotingMethod::Approval, 2, VoteRules::default());
+        assert!(!accepts(&approval, &[2, 0, 0]));
+        assert!(!accepts(&approval, &[0, 0, 0]));
+        assert!(!accepts(&approval, &[1, -1, 0]));
+        let score = poll(VotingMethod::Score, 2, VoteRules::default());
+        assert!(accepts(&score, &[MAX_SCORE, 0, 1]));
+        assert!(!accepts(&score, &[MAX_SCORE + 1, 0, 0]));
+        assert!(!accepts(&score, &[0, 0, 0]));
+    }
+
+    #[test]
+    fn ranks_run_without_gaps() {
+        let poll = poll(VotingMethod::RankedChoice, 2, VoteRules::default());
+        assert!(accepts(&poll, &[2, 1, 0]));
+        assert!(accepts(&poll, &[1, 3, 2]));
+        assert!(!accepts(&poll, &[1, 3, 0]));
+        assert!(!accepts(&poll, &[1, 1, 2]));
+        assert!(!accepts(&poll, &[0, 0, 0]));
+    }
+}